@@ -0,0 +1,26 @@
+#!/bin/sh
+# Rebuild resources/breached-passwords.txt.gz (see password_policy.rs) from a
+# most-common-first password list: one password per line, lowercased,
+# duplicates dropped keeping the first (most common) position.
+#
+#   scripts/update-breached-passwords.sh [URL or local file]
+#
+# Defaults to SecLists' top 10,000 list.
+set -eu
+
+SOURCE="${1:-https://raw.githubusercontent.com/danielmiessler/SecLists/master/Passwords/Common-Credentials/10k-most-common.txt}"
+OUT="$(dirname "$0")/../resources/breached-passwords.txt.gz"
+
+case "$SOURCE" in
+  http://* | https://*) fetch() { curl -fsSL "$SOURCE"; } ;;
+  *) fetch() { cat "$SOURCE"; } ;;
+esac
+
+fetch \
+  | tr -d '\r' \
+  | tr '[:upper:]' '[:lower:]' \
+  | awk 'length($0) > 0 && !seen[$0]++' \
+  | gzip -9n > "$OUT.tmp"
+mv "$OUT.tmp" "$OUT"
+
+echo "$(gzip -dc "$OUT" | wc -l) passwords written to $OUT"
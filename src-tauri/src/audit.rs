@@ -0,0 +1,374 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use tauri::State;
+use tauri_plugin_sql::DbInstances;
+
+use crate::auth::now_secs;
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+
+// ---------------------------------------------------------------------------
+// Append-only audit trail (`audit_log`, migration v8).
+//
+// Each row stores the hash of the row before it (`prev_hash`) and its own
+// hash over its contents plus `prev_hash`, so editing or deleting a row
+// breaks the chain from that point on. Triggers reject UPDATE and DELETE,
+// and the unique index on prev_hash means two racing writers cannot fork
+// the chain — the loser's insert fails instead.
+// ---------------------------------------------------------------------------
+
+// prev_hash of the first row
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditEvent {
+    LoginSucceeded,
+    LoginFailed,
+    Logout,
+    PasswordChanged,
+    SessionCreated,
+    SessionUpdated,
+    SessionDeleted,
+    Export,
+    AccountDeleted,
+    ViewerAdded,
+    ViewerRemoved,
+}
+
+impl AuditEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditEvent::LoginSucceeded => "login_succeeded",
+            AuditEvent::LoginFailed => "login_failed",
+            AuditEvent::Logout => "logout",
+            AuditEvent::PasswordChanged => "password_changed",
+            AuditEvent::SessionCreated => "session_created",
+            AuditEvent::SessionUpdated => "session_updated",
+            AuditEvent::SessionDeleted => "session_deleted",
+            AuditEvent::Export => "export",
+            AuditEvent::AccountDeleted => "account_deleted",
+            AuditEvent::ViewerAdded => "viewer_added",
+            AuditEvent::ViewerRemoved => "viewer_removed",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub created_at: i64, // unix seconds
+    pub user_id: Option<i64>,
+    pub username: String,
+    pub event: String,
+    pub details: String, // JSON object
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub next_before_id: Option<i64>, // pass back as `before_id` for the next page
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub checked: i64,
+    pub first_broken_id: Option<i64>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportRecord {
+    pub format: String, // e.g. "csv"
+    pub session_count: i64,
+}
+
+// Hash over the row contents, JSON-encoded so field boundaries are unambiguous
+fn entry_hash(
+    prev_hash: &str,
+    created_at: i64,
+    user_id: Option<i64>,
+    username: &str,
+    event: &str,
+    details: &str,
+) -> String {
+    let canonical = json!([prev_hash, created_at, user_id, username, event, details]).to_string();
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+// Append an entry. Takes a connection so callers can write the entry in the
+// same transaction as the change it describes; that transaction must come
+// from `db::begin_write`, so no other append can take the same tail between
+// the read below and the insert.
+pub async fn record(
+    conn: &mut SqliteConnection,
+    user_id: Option<i64>,
+    username: &str,
+    event: AuditEvent,
+    details: Value,
+) -> Result<()> {
+    let last: Option<(String,)> = sqlx::query_as("SELECT hash FROM audit_log ORDER BY id DESC LIMIT 1")
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(DashlensError::database("read audit log"))?;
+    let prev_hash = last.map(|(hash,)| hash).unwrap_or_else(|| GENESIS_HASH.to_string());
+
+    let created_at = now_secs();
+    let details = details.to_string();
+    let hash = entry_hash(&prev_hash, created_at, user_id, username, event.as_str(), &details);
+
+    sqlx::query(
+        "INSERT INTO audit_log (created_at, user_id, username, event, details, prev_hash, hash)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(created_at)
+    .bind(user_id)
+    .bind(username)
+    .bind(event.as_str())
+    .bind(&details)
+    .bind(&prev_hash)
+    .bind(&hash)
+    .execute(&mut *conn)
+    .await
+    .map_err(DashlensError::database("write audit log"))?;
+    Ok(())
+}
+
+// `record` for callers without a transaction of their own
+pub async fn record_with_pool(
+    pool: &Pool<Sqlite>,
+    user_id: Option<i64>,
+    username: &str,
+    event: AuditEvent,
+    details: Value,
+) -> Result<()> {
+    let mut tx = crate::db::begin_write(pool).await?;
+    record(&mut tx, user_id, username, event, details).await?;
+    tx.commit().await.map_err(DashlensError::database("write audit log"))?;
+    Ok(())
+}
+
+// Walk the whole chain in order and report the first row whose prev_hash or
+// hash does not match
+pub async fn verify_chain(pool: &Pool<Sqlite>) -> Result<AuditVerification> {
+    let sql = "SELECT id, created_at, user_id, username, event, details, prev_hash, hash
+                 FROM audit_log ORDER BY id";
+    let entries: Vec<AuditEntry> = sqlx::query_as(sql)
+        .fetch_all(pool)
+        .await
+        .map_err(DashlensError::database("read audit log"))?;
+
+    let mut expected_prev = GENESIS_HASH.to_string();
+    let mut checked = 0;
+    for entry in entries {
+        let reason = if entry.prev_hash != expected_prev {
+            Some("previous entry is missing or was altered")
+        } else if entry.hash
+            != entry_hash(
+                &entry.prev_hash,
+                entry.created_at,
+                entry.user_id,
+                &entry.username,
+                &entry.event,
+                &entry.details,
+            )
+        {
+            Some("entry contents were altered")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            return Ok(AuditVerification {
+                valid: false,
+                checked,
+                first_broken_id: Some(entry.id),
+                reason: Some(reason.to_string()),
+            });
+        }
+        expected_prev = entry.hash;
+        checked += 1;
+    }
+
+    Ok(AuditVerification {
+        valid: true,
+        checked,
+        first_broken_id: None,
+        reason: None,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+// Newest-first page of the logged-in user's entries, including failed logins
+// made against their username. `before_id` is the cursor from the previous page.
+#[tauri::command]
+pub async fn list_audit_log(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    before_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<AuditPage> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let pool = crate::db::pool(&db).await?;
+
+    let entries: Vec<AuditEntry> = sqlx::query_as(
+        "SELECT id, created_at, user_id, username, event, details, prev_hash, hash
+           FROM audit_log
+          WHERE (user_id = $1 OR (user_id IS NULL AND username = $2))
+            AND ($3 IS NULL OR id < $3)
+          ORDER BY id DESC
+          LIMIT $4",
+    )
+    .bind(user.user_id)
+    .bind(&user.username)
+    .bind(before_id)
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(DashlensError::database("read audit log"))?;
+
+    let next_before_id = if entries.len() as i64 == limit {
+        entries.last().map(|entry| entry.id)
+    } else {
+        None
+    };
+    Ok(AuditPage { entries, next_before_id })
+}
+
+#[tauri::command]
+pub async fn verify_audit_log(
+    _user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<AuditVerification> {
+    let pool = crate::db::pool(&db).await?;
+    verify_chain(&pool).await
+}
+
+// Exports are assembled in the webview; it reports each one here
+#[tauri::command]
+pub async fn record_export(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    export: ExportRecord,
+) -> Result<()> {
+    let pool = crate::db::pool(&db).await?;
+    let details = json!({ "format": export.format, "session_count": export.session_count });
+    record_with_pool(&pool, Some(user.user_id), &user.username, AuditEvent::Export, details).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn chain_of(entries: usize) -> Pool<Sqlite> {
+        let pool = crate::db::memory_pool().await;
+        for i in 0..entries {
+            let details = json!({ "session_id": i });
+            record_with_pool(&pool, Some(1), "driver", AuditEvent::SessionCreated, details).await.unwrap();
+        }
+        pool
+    }
+
+    // Tampering needs the append-only triggers out of the way first
+    async fn drop_triggers(pool: &Pool<Sqlite>) {
+        sqlx::raw_sql("DROP TRIGGER audit_log_no_update; DROP TRIGGER audit_log_no_delete;")
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn broken_at(pool: &Pool<Sqlite>) -> (Option<i64>, i64, Option<String>) {
+        let verification = verify_chain(pool).await.unwrap();
+        assert_eq!(verification.valid, verification.first_broken_id.is_none());
+        (verification.first_broken_id, verification.checked, verification.reason)
+    }
+
+    #[test]
+    fn intact_chain_verifies() {
+        tauri::async_runtime::block_on(async {
+            let pool = chain_of(4).await;
+            assert_eq!(broken_at(&pool).await, (None, 4, None));
+
+            let (first_prev,): (String,) = sqlx::query_as("SELECT prev_hash FROM audit_log WHERE id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(first_prev, GENESIS_HASH);
+        });
+    }
+
+    #[test]
+    fn empty_log_verifies() {
+        tauri::async_runtime::block_on(async {
+            let pool = chain_of(0).await;
+            assert_eq!(broken_at(&pool).await, (None, 0, None));
+        });
+    }
+
+    #[test]
+    fn edited_details_are_reported_at_that_entry() {
+        tauri::async_runtime::block_on(async {
+            let pool = chain_of(4).await;
+            drop_triggers(&pool).await;
+            sqlx::query("UPDATE audit_log SET details = '{\"session_id\":99}' WHERE id = 2")
+                .execute(&pool)
+                .await
+                .unwrap();
+            assert_eq!(
+                broken_at(&pool).await,
+                (Some(2), 1, Some("entry contents were altered".to_string()))
+            );
+        });
+    }
+
+    #[test]
+    fn edited_prev_hash_is_reported_at_that_entry() {
+        tauri::async_runtime::block_on(async {
+            let pool = chain_of(4).await;
+            drop_triggers(&pool).await;
+            sqlx::query("UPDATE audit_log SET prev_hash = $1 WHERE id = 3")
+                .bind("f".repeat(64))
+                .execute(&pool)
+                .await
+                .unwrap();
+            assert_eq!(
+                broken_at(&pool).await,
+                (Some(3), 2, Some("previous entry is missing or was altered".to_string()))
+            );
+        });
+    }
+
+    #[test]
+    fn deleted_entry_is_reported_at_the_next_one() {
+        tauri::async_runtime::block_on(async {
+            let pool = chain_of(4).await;
+            drop_triggers(&pool).await;
+            sqlx::query("DELETE FROM audit_log WHERE id = 2").execute(&pool).await.unwrap();
+            assert_eq!(
+                broken_at(&pool).await,
+                (Some(3), 1, Some("previous entry is missing or was altered".to_string()))
+            );
+        });
+    }
+
+    #[test]
+    fn triggers_reject_updates_and_deletes() {
+        tauri::async_runtime::block_on(async {
+            let pool = chain_of(2).await;
+            let update = sqlx::query("UPDATE audit_log SET details = '{}' WHERE id = 1").execute(&pool).await;
+            assert!(update.unwrap_err().to_string().contains("audit_log is append-only"));
+            let delete = sqlx::query("DELETE FROM audit_log WHERE id = 2").execute(&pool).await;
+            assert!(delete.unwrap_err().to_string().contains("audit_log is append-only"));
+            assert_eq!(broken_at(&pool).await, (None, 2, None));
+        });
+    }
+}
@@ -1,101 +1,1004 @@
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use sqlx::SqliteConnection;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_sql::DbInstances;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Mutex;
-use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
-};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::audit::{self, AuditEvent};
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+use crate::password::{self, Argon2Policy, CalibrationResult};
+use crate::roles::{self, Role};
+use crate::secret::SecretString;
+use crate::throttle::Attempt;
+use crate::vault::{self, DataKey};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AuthSession {
     pub user_id: i64,
     pub username: String,
+    pub role: Role,
+    pub owner_id: i64, // whose data the session reads; user_id for owners
     pub logged_in: bool,
+    pub issued_at: i64,     // unix seconds
+    pub last_activity: i64, // unix seconds
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct VerifyPasswordRequest {
-    pub password: String,
-    pub hash: String,
+// Idle timeout applied until the frontend configures one
+pub const DEFAULT_IDLE_TIMEOUT_SECS: i64 = 5 * 60;
+const MIN_IDLE_TIMEOUT_SECS: i64 = 30;
+const MAX_IDLE_TIMEOUT_SECS: i64 = 24 * 60 * 60;
+const IDLE_TIMEOUT_SETTING: &str = "idle_timeout_secs";
+
+// Wrong PINs allowed before the PIN stops working until the next password login
+pub const PIN_MAX_ATTEMPTS: i64 = 5;
+const PIN_MIN_DIGITS: usize = 4;
+const PIN_MAX_DIGITS: usize = 8;
+
+// Hard cap on a session's lifetime, regardless of activity
+pub const MAX_SESSION_AGE_SECS: i64 = 12 * 60 * 60;
+
+// How often the background watcher checks for a lapsed session
+const LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// Emitted to the frontend when the watcher clears a lapsed session
+pub const LOCKED_EVENT: &str = "auth://locked";
+
+// Emitted whenever the logged-in user changes (login, logout, switch, lock)
+pub const CHANGED_EVENT: &str = "auth://changed";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AuthChangedEvent {
+    pub user: Option<AuthSession>, // None once nobody is logged in
+    pub reason: &'static str,      // "login" | "register" | "restore" | "pin" | "switch" | "logout" | "locked" | "deleted"
 }
 
-#[derive(Debug, Serialize)]
-pub struct HashResponse {
-    pub hash: String,
+#[derive(Debug, Serialize, Clone)]
+pub struct LockedEvent {
+    pub user_id: i64,
+    pub username: String,
+    pub reason: &'static str, // "idle" | "expired"
+}
+
+pub fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterRequest {
+    pub username: String,
+    pub password: SecretString,
 }
 
 #[derive(Debug, Serialize)]
-pub struct VerifyResponse {
-    pub valid: bool,
+pub struct RegisterResponse {
+    pub user: AuthSession,
+    pub recovery_codes: Vec<String>, // shown once; only hashes are stored
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: SecretString,
+    #[serde(default)]
+    pub remember: bool, // persist a token so restore_session can skip the login screen
+    #[serde(default)]
+    pub totp_code: Option<String>, // required once the user has enabled TOTP
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: SecretString,
+    pub new_password: SecretString,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPinRequest {
+    pub password: SecretString,
+    pub pin: SecretString,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnlockWithPinRequest {
+    pub pin: SecretString,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: SecretString,
 }
 
 pub struct AuthState {
     pub current_user: Mutex<Option<AuthSession>>,
+    pub idle_timeout_secs: AtomicI64,
+    data_key: Mutex<Option<DataKey>>, // unlocked at login for encrypted accounts (vault.rs)
 }
 
 impl AuthState {
     pub fn new() -> Self {
         Self {
             current_user: Mutex::new(None),
+            idle_timeout_secs: AtomicI64::new(DEFAULT_IDLE_TIMEOUT_SECS),
+            data_key: Mutex::new(None),
         }
     }
+
+    // Apply the idle timeout saved by set_idle_timeout; called once at startup.
+    // A missing or out-of-range value leaves the default in place.
+    pub async fn load_idle_timeout(&self, pool: &sqlx::Pool<sqlx::Sqlite>) -> Result<()> {
+        let stored = crate::settings::get(pool, IDLE_TIMEOUT_SETTING).await?;
+        if let Some(secs) = stored
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|secs| (MIN_IDLE_TIMEOUT_SECS..=MAX_IDLE_TIMEOUT_SECS).contains(secs))
+        {
+            self.idle_timeout_secs.store(secs, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    // Replace the current session with a fresh one for the given user.
+    // `role`/`owner_id` come from roles::lookup; `data_key` is the user's
+    // unlocked encryption key, if they have one.
+    pub fn start(
+        &self,
+        user_id: i64,
+        username: String,
+        (role, owner_id): (Role, i64),
+        data_key: Option<DataKey>,
+    ) -> Result<AuthSession> {
+        let now = now_secs();
+        let session = AuthSession {
+            user_id,
+            username,
+            role,
+            owner_id,
+            logged_in: true,
+            issued_at: now,
+            last_activity: now,
+        };
+        *self.current_user.lock()? = Some(session.clone());
+        *self.data_key.lock()? = data_key;
+        Ok(session)
+    }
+
+    // End the session and forget the encryption key, returning what was cleared
+    pub fn end(&self) -> Result<Option<AuthSession>> {
+        *self.data_key.lock()? = None;
+        Ok(self.current_user.lock()?.take())
+    }
+
+    pub fn data_key(&self) -> Result<Option<DataKey>> {
+        Ok(self.data_key.lock()?.clone())
+    }
+
+    pub fn set_data_key(&self, data_key: Option<DataKey>) -> Result<()> {
+        *self.data_key.lock()? = data_key;
+        Ok(())
+    }
+
+    // Why a session may no longer be used, if it has lapsed at `now`
+    fn lapse_reason(&self, session: &AuthSession, now: i64) -> Option<&'static str> {
+        if now - session.issued_at >= MAX_SESSION_AGE_SECS {
+            Some("expired")
+        } else if now - session.last_activity >= self.idle_timeout_secs.load(Ordering::Relaxed) {
+            Some("idle")
+        } else {
+            None
+        }
+    }
+
+    // Current session if it is still live; does not count as activity
+    pub fn active_session(&self) -> Result<Option<AuthSession>> {
+        let now = now_secs();
+        Ok(self
+            .current_user
+            .lock()?
+            .clone()
+            .filter(|session| self.lapse_reason(session, now).is_none()))
+    }
+
+    // Live session, counting the call as activity (pushes back the idle lock).
+    // Commands normally get this through guard::CurrentUser.
+    pub fn touch(&self) -> Result<AuthSession> {
+        let now = now_secs();
+        let mut guard = self.current_user.lock()?;
+        let session = guard.as_mut().ok_or_else(DashlensError::not_logged_in)?;
+        if self.lapse_reason(session, now).is_some() {
+            return Err(DashlensError::Unauthenticated("Session locked".into()));
+        }
+        session.last_activity = now;
+        Ok(session.clone())
+    }
+
+    // Clear the session if it has lapsed, returning what was cleared.
+    // A poisoned lock is recovered rather than killing the watcher thread.
+    fn lock_if_lapsed(&self) -> Option<LockedEvent> {
+        let now = now_secs();
+        let mut guard = self.current_user.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let reason = guard.as_ref().and_then(|session| self.lapse_reason(session, now))?;
+        let session = guard.take()?;
+        *self.data_key.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+        Some(LockedEvent {
+            user_id: session.user_id,
+            username: session.username,
+            reason,
+        })
+    }
+}
+
+// Background task that locks a lapsed session and notifies the frontend.
+// Runs on its own thread for the lifetime of the app.
+pub fn spawn_lock_watcher<R: Runtime>(app: AppHandle<R>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(LOCK_CHECK_INTERVAL);
+        let state = app.state::<AuthState>();
+        if let Some(event) = state.lock_if_lapsed() {
+            let _ = app.emit(LOCKED_EVENT, event);
+            emit_changed(&app, None, "locked");
+        }
+    });
 }
 
-// Tauri command to hash a password using Argon2
+pub fn emit_changed<R: Runtime>(app: &AppHandle<R>, user: Option<&AuthSession>, reason: &'static str) {
+    let event = AuthChangedEvent {
+        user: user.cloned(),
+        reason,
+    };
+    let _ = app.emit(CHANGED_EVENT, event);
+}
+
+// Bookkeeping once a session has started: list the profile as recently used
+// on this device and tell the frontend who is logged in now
+async fn signed_in<R: Runtime>(
+    app: &AppHandle<R>,
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    session: AuthSession,
+    reason: &'static str,
+) -> Result<AuthSession> {
+    crate::profiles::touch(pool, session.user_id, &session.username).await?;
+    emit_changed(app, Some(&session), reason);
+    Ok(session)
+}
+
+// ---------------------------------------------------------------------------
+// Quick-unlock PIN
+//
+// An optional 4–8 digit PIN, hashed with Argon2 into users.pin_hash. It can
+// only unlock the account remembered on this device (remember.rs): with a PIN
+// set, restore_session stops logging in silently and answers `pin_required`
+// instead. After PIN_MAX_ATTEMPTS wrong PINs it stops working until the
+// next successful password login.
+// ---------------------------------------------------------------------------
+
+fn validate_pin(pin: &str) -> Result<()> {
+    let digits_only = pin.chars().all(|c| c.is_ascii_digit());
+    if !digits_only || !(PIN_MIN_DIGITS..=PIN_MAX_DIGITS).contains(&pin.len()) {
+        return Err(DashlensError::Validation(format!(
+            "PIN must be {} to {} digits",
+            PIN_MIN_DIGITS, PIN_MAX_DIGITS
+        )));
+    }
+    Ok(())
+}
+
+// Set or replace the logged-in user's PIN; requires the account password
 #[tauri::command]
-pub async fn hash_password(password: String) -> Result<HashResponse, String> {
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    
-    let hash = argon2
-        .hash_password(password.as_bytes(), &salt)
-        .map(|hash| hash.to_string())
-        .map_err(|e| format!("Failed to hash password: {}", e))?;
-    
-    Ok(HashResponse { hash })
+pub async fn set_pin(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    request: SetPinRequest,
+) -> Result<()> {
+    validate_pin(&request.pin)?;
+    if user.data_key.is_some() {
+        // Encrypted accounts are never remembered, so a PIN could not be used
+        return Err(DashlensError::Validation(
+            "PIN unlock is not available while encryption is enabled".into(),
+        ));
+    }
+    let pool = crate::db::pool(&db).await?;
+
+    let mut conn = pool.acquire().await.map_err(DashlensError::database("open connection"))?;
+    vault::require_password(&mut conn, user.user_id, &request.password).await?;
+
+    let policy = password::current_policy(&pool).await?;
+    let pin_hash = password::hash(&request.pin, &policy)?;
+    sqlx::query("UPDATE users SET pin_hash = $1, pin_failed_count = 0 WHERE id = $2")
+        .bind(&pin_hash)
+        .bind(user.user_id)
+        .execute(&pool)
+        .await
+        .map_err(DashlensError::database("store PIN"))?;
+    Ok(())
 }
 
-// Tauri command to verify a password against a hash
 #[tauri::command]
-pub async fn verify_password(request: VerifyPasswordRequest) -> Result<VerifyResponse, String> {
-    let parsed_hash = PasswordHash::new(&request.hash)
-        .map_err(|e| format!("Failed to parse hash: {}", e))?;
-    
-    let argon2 = Argon2::default();
-    
-    let valid = argon2.verify_password(request.password.as_bytes(), &parsed_hash).is_ok();
-    
-    Ok(VerifyResponse { valid })
+pub async fn remove_pin(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<()> {
+    let pool = crate::db::pool(&db).await?;
+    sqlx::query("UPDATE users SET pin_hash = NULL, pin_failed_count = 0 WHERE id = $1")
+        .bind(user.user_id)
+        .execute(&pool)
+        .await
+        .map_err(DashlensError::database("remove PIN"))?;
+    Ok(())
 }
 
-// Session management commands
 #[tauri::command]
-pub async fn set_session(
+pub async fn pin_status(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<bool> {
+    let pool = crate::db::pool(&db).await?;
+    let (enabled,): (bool,) = sqlx::query_as("SELECT pin_hash IS NOT NULL FROM users WHERE id = $1")
+        .bind(user.user_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(DashlensError::database("read PIN settings"))?;
+    Ok(enabled)
+}
+
+// (pin_hash, failed attempts) for a user with a PIN set
+async fn pin_of(pool: &sqlx::Pool<sqlx::Sqlite>, user_id: i64) -> Result<Option<(String, i64)>> {
+    let row: Option<(Option<String>, i64)> =
+        sqlx::query_as("SELECT pin_hash, pin_failed_count FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(DashlensError::database("read PIN settings"))?;
+    Ok(row.and_then(|(pin_hash, failed)| pin_hash.map(|pin_hash| (pin_hash, failed))))
+}
+
+// Count a PIN attempt before the PIN is checked, under SQLite's write lock,
+// so concurrent unlocks cannot each slip past the limit with a guess of
+// their own. Returns (pin_hash, attempts used including this one), or None
+// when no PIN is set or its attempts are used up.
+async fn claim_pin_attempt(pool: &sqlx::Pool<sqlx::Sqlite>, user_id: i64) -> Result<Option<(String, i64)>> {
+    let mut tx = crate::db::begin_write(pool).await?;
+    let row: Option<(String, i64)> = sqlx::query_as(
+        "UPDATE users SET pin_failed_count = pin_failed_count + 1
+         WHERE id = $1 AND pin_hash IS NOT NULL AND pin_failed_count < $2
+         RETURNING pin_hash, pin_failed_count",
+    )
+    .bind(user_id)
+    .bind(PIN_MAX_ATTEMPTS)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(DashlensError::database("record PIN attempt"))?;
+    tx.commit().await.map_err(DashlensError::database("record PIN attempt"))?;
+    Ok(row)
+}
+
+// Log the remembered account back in with its PIN. Fails as unauthenticated
+// (i.e. "use your password") when nothing is remembered, no PIN is set, or
+// the PIN is locked out.
+#[tauri::command]
+pub async fn unlock_with_pin(
+    app: AppHandle,
     state: State<'_, AuthState>,
-    session: AuthSession,
-) -> Result<(), String> {
-    *state.current_user.lock().unwrap() = Some(session);
+    db: State<'_, DbInstances>,
+    request: UnlockWithPinRequest,
+) -> Result<AuthSession> {
+    let pool = crate::db::pool(&db).await?;
+    let use_password = || DashlensError::Unauthenticated("Log in with your password".into());
+
+    let (user_id, username) = crate::remember::restore(&app, &pool).await?.ok_or_else(use_password)?;
+    let (pin_hash, attempts) = claim_pin_attempt(&pool, user_id).await?.ok_or_else(use_password)?;
+
+    if !password::verify(&request.pin, &pin_hash)? {
+        let details = serde_json::json!({ "reason": "invalid_pin", "attempts": attempts });
+        audit::record_with_pool(&pool, Some(user_id), &username, AuditEvent::LoginFailed, details).await?;
+        return Err(if attempts >= PIN_MAX_ATTEMPTS {
+            DashlensError::Unauthenticated("Too many wrong PINs. Log in with your password.".into())
+        } else {
+            DashlensError::InvalidCredentials("Incorrect PIN".into())
+        });
+    }
+
+    sqlx::query("UPDATE users SET pin_failed_count = 0 WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(DashlensError::database("record PIN attempt"))?;
+    let details = serde_json::json!({ "method": "pin" });
+    audit::record_with_pool(&pool, Some(user_id), &username, AuditEvent::LoginSucceeded, details).await?;
+    let access = roles::lookup(&pool, user_id).await?;
+    let session = state.start(user_id, username, access, None)?;
+    signed_in(&app, &pool, session, "pin").await
+}
+
+// Register a new user and log them in.
+// The users row is written here rather than by the webview so that the
+// session stored in AuthState always corresponds to a real account.
+// The response carries the user's initial recovery codes.
+#[tauri::command]
+pub async fn register(
+    app: AppHandle,
+    state: State<'_, AuthState>,
+    db: State<'_, DbInstances>,
+    request: RegisterRequest,
+) -> Result<RegisterResponse> {
+    let username = crate::username::validate(&request.username)?;
+    crate::password_policy::enforce(&request.password, &username)?;
+
+    let pool = crate::db::pool(&db).await?;
+    let (user_id, recovery_codes) = create_owner(&pool, &username, &request.password).await?;
+
+    let session = state.start(user_id, username, (Role::Owner, user_id), None)?;
+    Ok(RegisterResponse {
+        user: signed_in(&app, &pool, session, "register").await?,
+        recovery_codes,
+    })
+}
+
+// Write a new owner account and its first recovery codes. The name is
+// checked under the write lock, so of two concurrent registrations for the
+// same name one gets Conflict rather than a constraint error.
+async fn create_owner(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    username: &str,
+    password: &str,
+) -> Result<(i64, Vec<String>)> {
+    let policy = password::current_policy(pool).await?;
+    let password_hash = password::hash(password, &policy)?;
+
+    let mut tx = crate::db::begin_write(pool).await?;
+
+    let existing: Option<(i64,)> = sqlx::query_as("SELECT id FROM users WHERE username = $1 COLLATE NOCASE")
+        .bind(username)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(DashlensError::database("look up user"))?;
+    if existing.is_some() {
+        return Err(DashlensError::Conflict("Username already exists".into()));
+    }
+
+    let result = sqlx::query("INSERT INTO users (username, password_hash) VALUES ($1, $2)")
+        .bind(username)
+        .bind(&password_hash)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("create user"))?;
+
+    let user_id = result.last_insert_rowid();
+
+    // Sessions recorded before any account existed (v3 backfill had no user
+    // to assign them to) belong to whoever registers first.
+    sqlx::query("UPDATE sessions SET user_id = $1 WHERE user_id IS NULL")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("claim existing sessions"))?;
+
+    let recovery_codes = crate::recovery::replace_codes(&mut tx, user_id, None).await?;
+
+    tx.commit().await.map_err(DashlensError::database("create user"))?;
+    Ok((user_id, recovery_codes))
+}
+
+// Audit a failed login; escalates to Locked when the attempt, already
+// counted by `claim_attempt`, triggers a wait. `user_id` is set when the
+// username exists.
+async fn login_failed(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    username: &str,
+    user_id: Option<i64>,
+    wait_if_failed: i64,
+    error: DashlensError,
+) -> DashlensError {
+    let details = serde_json::json!({ "reason": error.code() });
+    if let Err(e) = audit::record_with_pool(pool, user_id, username, AuditEvent::LoginFailed, details).await {
+        return e;
+    }
+    if wait_if_failed > 0 {
+        DashlensError::Locked {
+            retry_after_secs: wait_if_failed,
+        }
+    } else {
+        error
+    }
+}
+
+// Verify credentials against the users table and start a session.
+// Unknown usernames and wrong passwords produce the same error, and both
+// run an Argon2 verification, so neither the response nor its timing
+// reveals which accounts exist. Repeated failures are
+// throttled per canonical username (see throttle.rs, username.rs).
+#[tauri::command]
+pub async fn login(
+    app: AppHandle,
+    state: State<'_, AuthState>,
+    db: State<'_, DbInstances>,
+    request: LoginRequest,
+) -> Result<AuthSession> {
+    let pool = crate::db::pool(&db).await?;
+    let (user_id, username, data_key) = authenticate(&app, &pool, &request).await?;
+    let access = roles::lookup(&pool, user_id).await?;
+    let session = state.start(user_id, username, access, data_key)?;
+    signed_in(&app, &pool, session, "login").await
+}
+
+// Lock the current user and log in as another. The current session ends
+// whether or not the target's credentials check out, so a shared device is
+// never left on the previous driver's data; a second step (e.g. a TOTP code)
+// then goes through `login`.
+#[tauri::command]
+pub async fn switch_user(
+    app: AppHandle,
+    user: CurrentUser,
+    state: State<'_, AuthState>,
+    db: State<'_, DbInstances>,
+    request: LoginRequest,
+) -> Result<AuthSession> {
+    let pool = crate::db::pool(&db).await?;
+    let result = authenticate(&app, &pool, &request).await;
+
+    state.end()?;
+    let details = serde_json::json!({ "reason": "switch_user" });
+    audit::record_with_pool(&pool, Some(user.user_id), &user.username, AuditEvent::Logout, details).await?;
+
+    match result {
+        Ok((user_id, username, data_key)) => {
+            let access = roles::lookup(&pool, user_id).await?;
+            let session = state.start(user_id, username, access, data_key)?;
+            signed_in(&app, &pool, session, "switch").await
+        }
+        Err(e) => {
+            emit_changed(&app, None, "switch");
+            Err(e)
+        }
+    }
+}
+
+// Shared by login and switch_user: check credentials (with throttling and
+// TOTP), upgrade the hash, handle remember-me and audit the outcome. Returns
+// the user and, for encrypted accounts, their unlocked data key.
+async fn authenticate(
+    app: &AppHandle,
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    request: &LoginRequest,
+) -> Result<(i64, String, Option<DataKey>)> {
+    let login_name = crate::username::normalize(&request.username);
+
+    let wait_if_failed = match crate::throttle::claim_attempt(pool, &login_name).await? {
+        Attempt::Claimed { wait_if_failed } => wait_if_failed,
+        Attempt::Locked { retry_after_secs } => {
+            let details = serde_json::json!({ "reason": "locked" });
+            audit::record_with_pool(pool, None, &login_name, AuditEvent::LoginFailed, details).await?;
+            return Err(DashlensError::Locked { retry_after_secs });
+        }
+    };
+
+    let candidates = crate::username::login_candidates(pool, &request.username).await?;
+    let known_id = candidates.first().map(|(id, _, _)| *id);
+    if candidates.is_empty() {
+        password::verify_dummy(&request.password, &password::current_policy(pool).await?)?;
+    }
+    let mut user = None;
+    for candidate in candidates {
+        if password::verify(&request.password, &candidate.2)? {
+            user = Some(candidate);
+            break;
+        }
+    }
+    let Some((user_id, username, password_hash)) = user else {
+        let error = DashlensError::InvalidCredentials("Invalid credentials".into());
+        return Err(login_failed(pool, &login_name, known_id, wait_if_failed, error).await);
+    };
+
+    // Second factor, if enrolled. Asking for the code only after the password
+    // checks out avoids revealing 2FA status for unknown passwords.
+    if let Some((secret, last_used_step)) = crate::totp::enabled_secret(pool, user_id).await? {
+        let Some(code) = request.totp_code.as_deref().filter(|code| !code.trim().is_empty()) else {
+            // The password was right; only the code is missing
+            crate::throttle::refund_attempt(pool, &login_name).await?;
+            return Err(DashlensError::TotpRequired);
+        };
+        let step = crate::totp::verify_code(&secret, code, now_secs(), last_used_step);
+        let accepted = match step {
+            Some(step) => crate::totp::mark_used(pool, user_id, step).await?,
+            None => false,
+        };
+        if !accepted {
+            let error = DashlensError::InvalidCredentials("Invalid authentication code".into());
+            return Err(login_failed(pool, &login_name, Some(user_id), wait_if_failed, error).await);
+        }
+    }
+
+    crate::throttle::clear_failures(pool, &login_name).await?;
+    crate::username::mark_resolved(pool, user_id).await?;
+
+    // A password login re-arms a PIN that was locked out
+    sqlx::query("UPDATE users SET pin_failed_count = 0 WHERE id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(DashlensError::database("reset PIN attempts"))?;
+
+    // Transparently upgrade hashes made under an older policy. This is
+    // best-effort: the old hash still verifies, so a failure here must not
+    // block the login.
+    let policy = password::current_policy(pool).await?;
+    if policy.needs_rehash(&password_hash) {
+        if let Ok(new_hash) = password::hash(&request.password, &policy) {
+            let _ = sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+                .bind(&new_hash)
+                .bind(user_id)
+                .execute(pool)
+                .await;
+        }
+    }
+
+    let mut conn = pool.acquire().await.map_err(DashlensError::database("open connection"))?;
+    let data_key = vault::unlock(&mut conn, user_id, &request.password).await?;
+    drop(conn);
+
+    // Any previously remembered account is forgotten on an explicit login.
+    // Encrypted accounts are never remembered: a restored session would have
+    // no password to unlock the data key with.
+    crate::remember::revoke(app, pool).await?;
+    if request.remember && data_key.is_none() {
+        crate::remember::issue(app, pool, user_id).await?;
+    }
+
+    let details = serde_json::json!({ "remember": request.remember });
+    audit::record_with_pool(pool, Some(user_id), &username, AuditEvent::LoginSucceeded, details).await?;
+
+    Ok((user_id, username, data_key))
+}
+
+// Change the logged-in user's password after re-verifying the current one.
+// Remember-me tokens issued under the old password are revoked, and an
+// encrypted account's data key is re-wrapped under the new password.
+#[tauri::command]
+pub async fn change_password(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    request: ChangePasswordRequest,
+) -> Result<()> {
+    let user_id = user.user_id;
+    crate::password_policy::enforce(&request.new_password, &user.username)?;
+
+    let pool = crate::db::pool(&db).await?;
+
+    let mut conn = pool.acquire().await.map_err(DashlensError::database("open connection"))?;
+    vault::require_password(&mut conn, user_id, &request.current_password)
+        .await
+        .map_err(|e| match e {
+            DashlensError::InvalidCredentials(_) => {
+                DashlensError::InvalidCredentials("Current password is incorrect".into())
+            }
+            e => e,
+        })?;
+
+    let policy = password::current_policy(&pool).await?;
+    let new_hash = password::hash(&request.new_password, &policy)?;
+
+    let mut tx = crate::db::begin_write(&pool).await?;
+    sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+        .bind(&new_hash)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("update password"))?;
+    sqlx::query("DELETE FROM auth_tokens WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("revoke remember-me tokens"))?;
+    vault::rewrap(&mut tx, user_id, &request.current_password, &request.new_password, &policy).await?;
+    let details = serde_json::json!({ "method": "change_password" });
+    audit::record(&mut tx, Some(user_id), &user.username, AuditEvent::PasswordChanged, details).await?;
+    tx.commit().await.map_err(DashlensError::database("update password"))?;
+
+    Ok(())
+}
+
+// Delete a user row and the auth data tied to it: throttling state, tokens,
+// TOTP, recovery codes and the encryption key. Earnings rows are left to the
+// caller. The pool runs with foreign_keys on (sqlx's default), so most of
+// these would cascade anyway; they are listed so the delete does not hinge
+// on that connection setting.
+pub(crate) async fn delete_user_rows(conn: &mut SqliteConnection, user_id: i64, username: &str) -> Result<()> {
+    sqlx::query("DELETE FROM login_attempts WHERE username = $1")
+        .bind(username)
+        .execute(&mut *conn)
+        .await
+        .map_err(DashlensError::database("delete login attempts"))?;
+    for (sql, action) in [
+        ("DELETE FROM auth_tokens WHERE user_id = $1", "delete remember-me tokens"),
+        ("DELETE FROM user_totp WHERE user_id = $1", "delete two-factor settings"),
+        ("DELETE FROM recovery_codes WHERE user_id = $1", "delete recovery codes"),
+        ("DELETE FROM user_keys WHERE user_id = $1", "delete encryption key"),
+        ("DELETE FROM users WHERE id = $1", "delete user"),
+    ] {
+        sqlx::query(sql)
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(DashlensError::database(action))?;
+    }
     Ok(())
 }
 
+// Permanently delete the logged-in user after re-verifying their password.
+// Their sessions/offers and auth data go in one transaction; when no other
+// account remains, every session (including unowned pre-account rows) is
+// removed too. The database is then VACUUMed so freed pages do not keep
+// earnings on disk, and the session is cleared. The audit log is kept.
+#[tauri::command]
+pub async fn delete_account(
+    app: AppHandle,
+    user: CurrentUser,
+    state: State<'_, AuthState>,
+    db: State<'_, DbInstances>,
+    request: DeleteAccountRequest,
+) -> Result<()> {
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let mut conn = pool.acquire().await.map_err(DashlensError::database("open connection"))?;
+    vault::require_password(&mut conn, user_id, &request.password).await?;
+
+    if roles::has_viewers(&pool, user_id).await? {
+        return Err(DashlensError::Conflict(
+            "Remove this account's viewers before deleting it".into(),
+        ));
+    }
+
+    // Revoke the token file first; its row goes with the user below
+    crate::remember::revoke(&app, &pool).await?;
+
+    let mut tx = crate::db::begin_write(&pool).await?;
+
+    let (other_users,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE id <> $1")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(DashlensError::database("count users"))?;
+    // With no other account left, unowned pre-account sessions go as well
+    let all_sessions = other_users == 0;
+    sqlx::query("DELETE FROM offers WHERE session_id IN (SELECT id FROM sessions WHERE user_id = $1 OR $2)")
+        .bind(user_id)
+        .bind(all_sessions)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("delete offers"))?;
+    sqlx::query("DELETE FROM sessions WHERE user_id = $1 OR $2")
+        .bind(user_id)
+        .bind(all_sessions)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("delete sessions"))?;
+    delete_user_rows(&mut tx, user_id, &user.username).await?;
+
+    let details = serde_json::json!({ "removed_all_sessions": all_sessions });
+    audit::record(&mut tx, Some(user_id), &user.username, AuditEvent::AccountDeleted, details).await?;
+    tx.commit().await.map_err(DashlensError::database("delete account"))?;
+
+    state.end()?;
+    crate::profiles::forget(&pool, user_id).await?;
+    emit_changed(&app, None, "deleted");
+
+    // Outside the transaction: VACUUM cannot run inside one
+    sqlx::query("VACUUM")
+        .execute(&pool)
+        .await
+        .map_err(DashlensError::database("compact database"))?;
+    Ok(())
+}
+
+// Argon2 parameters new password hashes are created with
+#[tauri::command]
+pub async fn get_argon2_policy(
+    _user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<Argon2Policy> {
+    let pool = crate::db::pool(&db).await?;
+    password::current_policy(&pool).await
+}
+
+// Benchmark the device and store Argon2 parameters hitting `target_ms` per
+// verification (default 250ms). Existing hashes are upgraded on next login.
+// Owners only: the policy applies to every account on the device.
+#[tauri::command]
+pub async fn calibrate_argon2(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    target_ms: Option<u64>,
+) -> Result<CalibrationResult> {
+    user.require_owner()?;
+    let target = target_ms
+        .map(Duration::from_millis)
+        .unwrap_or(password::DEFAULT_CALIBRATION_TARGET);
+    let result = tauri::async_runtime::spawn_blocking(move || password::calibrate(target))
+        .await
+        .map_err(|e| DashlensError::Internal(format!("Calibration failed: {}", e)))??;
+
+    let pool = crate::db::pool(&db).await?;
+    password::save_policy(&pool, &result.policy).await?;
+    Ok(result)
+}
+
+// Called at startup: log the remembered user back in if their token is
+// still valid. Returns None when there is nothing to restore, and fails with
+// `pin_required` when the account must first be unlocked with its PIN.
+#[tauri::command]
+pub async fn restore_session(
+    app: AppHandle,
+    state: State<'_, AuthState>,
+    db: State<'_, DbInstances>,
+) -> Result<Option<AuthSession>> {
+    if let Some(session) = state.active_session()? {
+        return Ok(Some(session));
+    }
+
+    let pool = crate::db::pool(&db).await?;
+    let Some((user_id, username)) = crate::remember::restore(&app, &pool).await? else {
+        return Ok(None);
+    };
+
+    // With a PIN set the remembered account must be unlocked with it; once
+    // the PIN is locked out, fall back to the login screen
+    match pin_of(&pool, user_id).await? {
+        Some((_, failed)) if failed >= PIN_MAX_ATTEMPTS => Ok(None),
+        Some(_) => Err(DashlensError::PinRequired { username }),
+        None => {
+            let access = roles::lookup(&pool, user_id).await?;
+            let session = state.start(user_id, username, access, None)?;
+            signed_in(&app, &pool, session, "restore").await.map(Some)
+        }
+    }
+}
+
+// Session management commands
 #[tauri::command]
 pub async fn clear_session(
+    app: AppHandle,
     state: State<'_, AuthState>,
-) -> Result<(), String> {
-    *state.current_user.lock().unwrap() = None;
+    db: State<'_, DbInstances>,
+) -> Result<()> {
+    let session = state.end()?;
+
+    // Logging out also revokes the remember-me token
+    let pool = crate::db::pool(&db).await?;
+    crate::remember::revoke(&app, &pool).await?;
+
+    if let Some(session) = session {
+        let details = serde_json::json!({});
+        audit::record_with_pool(&pool, Some(session.user_id), &session.username, AuditEvent::Logout, details).await?;
+        emit_changed(&app, None, "logout");
+    }
     Ok(())
 }
 
 #[tauri::command]
 pub async fn get_current_user(
     state: State<'_, AuthState>,
-) -> Result<Option<AuthSession>, String> {
-    Ok(state.current_user.lock().unwrap().clone())
+) -> Result<Option<AuthSession>> {
+    state.active_session()
 }
 
 #[tauri::command]
 pub async fn check_auth_status(
     state: State<'_, AuthState>,
-) -> Result<bool, String> {
-    Ok(state.current_user.lock().unwrap().is_some())
+) -> Result<bool> {
+    Ok(state.active_session()?.is_some())
+}
+
+// Record user activity from the frontend (input events) to defer the idle lock
+#[tauri::command]
+pub async fn touch_session(
+    _user: CurrentUser, // resolving the user is what records the activity
+) -> Result<()> {
+    Ok(())
+}
+
+// Configure how long the session may sit idle before it is locked; owners
+// only, as it applies to whoever uses the device next
+#[tauri::command]
+pub async fn set_idle_timeout(
+    user: CurrentUser,
+    state: State<'_, AuthState>,
+    db: State<'_, DbInstances>,
+    secs: i64,
+) -> Result<()> {
+    user.require_owner()?;
+    if !(MIN_IDLE_TIMEOUT_SECS..=MAX_IDLE_TIMEOUT_SECS).contains(&secs) {
+        return Err(DashlensError::Validation(format!(
+            "Idle timeout must be between {} and {} seconds",
+            MIN_IDLE_TIMEOUT_SECS, MAX_IDLE_TIMEOUT_SECS
+        )));
+    }
+    let pool = crate::db::pool(&db).await?;
+    crate::settings::set(&pool, IDLE_TIMEOUT_SETTING, &secs.to_string()).await?;
+    state.idle_timeout_secs.store(secs, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registering_a_taken_name_is_a_conflict() {
+        tauri::async_runtime::block_on(async {
+            let pool = crate::db::memory_pool().await;
+            let cheap = Argon2Policy {
+                m_cost: 64,
+                t_cost: 1,
+                p_cost: 1,
+                ..Argon2Policy::default()
+            };
+            password::save_policy(&pool, &cheap).await.unwrap();
+
+            let (user_id, codes) = create_owner(&pool, "driver", "first password").await.unwrap();
+            assert_eq!(codes.len(), 10);
+            // Usernames arrive canonical; NOCASE also catches stray capitals
+            for name in ["driver", "DRIVER"] {
+                let error = create_owner(&pool, name, "second password").await.unwrap_err();
+                assert_eq!(error.code(), "conflict");
+            }
+            let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users").fetch_one(&pool).await.unwrap();
+            assert_eq!(count, 1);
+            let (codes,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM recovery_codes WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(codes, 10);
+        });
+    }
+
+    #[test]
+    fn saved_idle_timeout_is_loaded() {
+        tauri::async_runtime::block_on(async {
+            let pool = crate::db::memory_pool().await;
+            let state = AuthState::new();
+            state.load_idle_timeout(&pool).await.unwrap();
+            assert_eq!(state.idle_timeout_secs.load(Ordering::Relaxed), DEFAULT_IDLE_TIMEOUT_SECS);
+
+            crate::settings::set(&pool, IDLE_TIMEOUT_SETTING, "900").await.unwrap();
+            state.load_idle_timeout(&pool).await.unwrap();
+            assert_eq!(state.idle_timeout_secs.load(Ordering::Relaxed), 900);
+
+            // Out of range or garbled: keep what is in effect
+            for stored in ["5", "not a number"] {
+                crate::settings::set(&pool, IDLE_TIMEOUT_SETTING, stored).await.unwrap();
+                state.load_idle_timeout(&pool).await.unwrap();
+                assert_eq!(state.idle_timeout_secs.load(Ordering::Relaxed), 900);
+            }
+        });
+    }
+
+    #[test]
+    fn pin_attempts_are_claimed_up_to_the_limit() {
+        tauri::async_runtime::block_on(async {
+            let pool = crate::db::memory_pool().await;
+            sqlx::query("INSERT INTO users (id, username, password_hash, pin_hash) VALUES (1, 'driver', 'x', 'pin')")
+                .execute(&pool)
+                .await
+                .unwrap();
+
+            for attempt in 1..=PIN_MAX_ATTEMPTS {
+                let claimed = claim_pin_attempt(&pool, 1).await.unwrap();
+                assert_eq!(claimed, Some(("pin".to_string(), attempt)));
+            }
+            // The next attempt is refused before any PIN is checked
+            assert_eq!(claim_pin_attempt(&pool, 1).await.unwrap(), None);
+
+            // No PIN, nothing to claim
+            sqlx::query("UPDATE users SET pin_hash = NULL, pin_failed_count = 0 WHERE id = 1")
+                .execute(&pool)
+                .await
+                .unwrap();
+            assert_eq!(claim_pin_attempt(&pool, 1).await.unwrap(), None);
+        });
+    }
 }
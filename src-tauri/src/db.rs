@@ -0,0 +1,111 @@
+use sqlx::{Pool, Sqlite, Transaction};
+use tauri_plugin_sql::{DbInstances, DbPool};
+
+use crate::error::{DashlensError, Result};
+
+// Connection string shared with the migrations in lib.rs. The pool is
+// preloaded by the SQL plugin (see `plugins.sql.preload` in tauri.conf.json),
+// so migrations have already run by the time any command reaches for it.
+// The webview gets no `sql:` permissions (capabilities/default.json); all
+// data access goes through the named commands, which
+// tests/capabilities.rs keeps that way.
+pub const DB_URL: &str = "sqlite:dashlens.db";
+
+// Borrow the plugin-managed SQLite pool for Rust-side queries
+pub async fn pool(instances: &DbInstances) -> Result<Pool<Sqlite>> {
+    let instances = instances.0.read().await;
+    match instances.get(DB_URL) {
+        Some(DbPool::Sqlite(pool)) => Ok(pool.clone()),
+        _ => Err(DashlensError::Internal(format!("Database {} is not loaded", DB_URL))),
+    }
+}
+
+// Transaction that takes SQLite's write lock up front (BEGIN IMMEDIATE), for
+// read-then-write sequences another writer must not interleave with. Other
+// writers wait on the busy timeout instead of failing mid-way.
+pub async fn begin_write(pool: &Pool<Sqlite>) -> Result<Transaction<'static, Sqlite>> {
+    pool.begin_with("BEGIN IMMEDIATE")
+        .await
+        .map_err(DashlensError::database("start transaction"))
+}
+
+// Fresh in-memory database with every migration applied. One connection,
+// since each `sqlite::memory:` connection is a database of its own.
+#[cfg(test)]
+pub(crate) async fn memory_pool() -> Pool<Sqlite> {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    for migration in crate::migrations() {
+        sqlx::raw_sql(migration.sql)
+            .execute(&pool)
+            .await
+            .unwrap_or_else(|e| panic!("migration {} failed: {}", migration.version, e));
+    }
+    pool
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{Connection, SqliteConnection};
+
+    async fn migrate(conn: &mut SqliteConnection, versions: std::ops::RangeInclusive<i64>) {
+        for migration in crate::migrations().into_iter().filter(|m| versions.contains(&m.version)) {
+            sqlx::raw_sql(migration.sql)
+                .execute(&mut *conn)
+                .await
+                .unwrap_or_else(|e| panic!("migration {} failed: {}", migration.version, e));
+        }
+    }
+
+    #[test]
+    fn migrations_are_in_order() {
+        let versions: Vec<i64> = crate::migrations().iter().map(|m| m.version).collect();
+        assert_eq!(versions, (1..=versions.len() as i64).collect::<Vec<_>>());
+    }
+
+    // v13 turns REAL dollars into INTEGER cents, rounding rather than
+    // truncating (0.29 * 100 is 28.999…), and leaves sealed text alone
+    #[test]
+    fn v13_converts_dollars_to_cents() {
+        tauri::async_runtime::block_on(async {
+            let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+            migrate(&mut conn, 1..=12).await;
+            sqlx::raw_sql(
+                "INSERT INTO users (id, username, password_hash) VALUES (1, 'driver', 'x');
+                 INSERT INTO sessions (id, user_id, date, total_earnings, base_pay, tips) VALUES
+                     (1, 1, '2024-03-01', 12.34, 10.25, 2.34),
+                     (2, 1, '2024-03-02', 20, NULL, 0.1),
+                     (3, 1, '2024-03-03', 'enc1:00ff', NULL, NULL),
+                     (4, 1, '2024-03-04', 0.29, 0.57, 1234567.89);
+                 INSERT INTO offers (session_id, store, total_earnings) VALUES
+                     (1, 'Taco Spot', 7.07), (2, 'Noodle House', 5), (3, NULL, 'enc1:abcd');",
+            )
+            .execute(&mut conn)
+            .await
+            .unwrap();
+            migrate(&mut conn, 13..=13).await;
+
+            // quote() shows INTEGER 1234, REAL 1234.0 and TEXT '…' apart
+            let sessions: Vec<String> = sqlx::query_scalar(
+                "SELECT quote(total_earnings) || ' ' || quote(base_pay) || ' ' || quote(tips)
+                   FROM sessions ORDER BY id",
+            )
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+            assert_eq!(
+                sessions,
+                ["1234 1025 234", "2000 NULL 10", "'enc1:00ff' NULL NULL", "29 57 123456789"]
+            );
+
+            let offers: Vec<String> = sqlx::query_scalar("SELECT quote(total_earnings) FROM offers ORDER BY id")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+            assert_eq!(offers, ["707", "500", "'enc1:abcd'"]);
+        });
+    }
+}
@@ -0,0 +1,970 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use tauri::State;
+use tauri_plugin_sql::DbInstances;
+
+use crate::audit::{self, AuditEvent};
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+use crate::money::Money;
+use crate::vault::{open_amount, seal_amount, DataKey};
+
+// ---------------------------------------------------------------------------
+// Entity types — mirror the v2 schema (see src/types/entries.ts); amounts
+// are Money, integer cents since v13.
+// Every query below is scoped to the logged-in user's id; offers inherit
+// ownership from their parent session. All writes go through the
+// validation and repository functions further down, whichever command they
+// come from.
+//
+// Earnings may be encrypted (vault.rs), so they are read as text into the
+// *Row types and decoded with the caller's data key.
+// ---------------------------------------------------------------------------
+
+pub(crate) const SESSION_COLUMNS: &str = "id, date, \
+     CAST(total_earnings AS TEXT) AS total_earnings, \
+     CAST(base_pay AS TEXT) AS base_pay, \
+     CAST(tips AS TEXT) AS tips, \
+     start_time, end_time, active_time, total_time, \
+     offers_count, deliveries, created_at";
+
+const OFFER_COLUMNS: &str = "id, session_id, store, \
+     CAST(total_earnings AS TEXT) AS total_earnings, created_at";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Session {
+    pub id: i64,
+    pub date: String,
+    pub total_earnings: Option<Money>,
+    pub base_pay: Option<Money>,
+    pub tips: Option<Money>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub active_time: Option<i64>,
+    pub total_time: Option<i64>,
+    pub offers_count: Option<i64>,
+    pub deliveries: Option<i64>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Offer {
+    pub id: i64,
+    pub session_id: i64,
+    pub store: Option<String>,
+    pub total_earnings: Option<Money>,
+    pub created_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct SessionRow {
+    id: i64,
+    date: String,
+    total_earnings: Option<String>,
+    base_pay: Option<String>,
+    tips: Option<String>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+    active_time: Option<i64>,
+    total_time: Option<i64>,
+    offers_count: Option<i64>,
+    deliveries: Option<i64>,
+    created_at: String,
+}
+
+impl SessionRow {
+    pub(crate) fn open(self, key: Option<&DataKey>) -> Result<Session> {
+        Ok(Session {
+            id: self.id,
+            date: self.date,
+            total_earnings: open_amount(key, self.total_earnings)?,
+            base_pay: open_amount(key, self.base_pay)?,
+            tips: open_amount(key, self.tips)?,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            active_time: self.active_time,
+            total_time: self.total_time,
+            offers_count: self.offers_count,
+            deliveries: self.deliveries,
+            created_at: self.created_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct OfferRow {
+    id: i64,
+    session_id: i64,
+    store: Option<String>,
+    total_earnings: Option<String>,
+    created_at: String,
+}
+
+impl OfferRow {
+    fn open(self, key: Option<&DataKey>) -> Result<Offer> {
+        Ok(Offer {
+            id: self.id,
+            session_id: self.session_id,
+            store: self.store,
+            total_earnings: open_amount(key, self.total_earnings)?,
+            created_at: self.created_at,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionWithOffers {
+    #[serde(flatten)]
+    pub session: Session,
+    pub offers: Vec<Offer>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionInsert {
+    pub date: String,
+    pub total_earnings: Option<Money>,
+    pub base_pay: Option<Money>,
+    pub tips: Option<Money>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub active_time: Option<i64>,
+    pub total_time: Option<i64>,
+    pub offers_count: Option<i64>,
+    pub deliveries: Option<i64>,
+}
+
+// Partial update — omitted (or null) fields keep their stored value
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct SessionUpdate {
+    pub date: Option<String>,
+    pub total_earnings: Option<Money>,
+    pub base_pay: Option<Money>,
+    pub tips: Option<Money>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub active_time: Option<i64>,
+    pub total_time: Option<i64>,
+    pub offers_count: Option<i64>,
+    pub deliveries: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OfferInsert {
+    pub store: Option<String>,
+    pub total_earnings: Option<Money>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct OfferUpdate {
+    pub store: Option<String>,
+    pub total_earnings: Option<Money>,
+}
+
+// ---------------------------------------------------------------------------
+// Validation
+// ---------------------------------------------------------------------------
+
+const MAX_AMOUNT: Money = Money::from_cents(10_000_000);
+const MAX_MINUTES: i64 = 24 * 60;
+const MAX_COUNT: i64 = 1_000;
+const MAX_STORE_LEN: usize = 200;
+
+fn invalid(message: String) -> DashlensError {
+    DashlensError::Validation(message)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn all_digits(parts: &[&str]) -> bool {
+    parts.iter().all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+// ISO "YYYY-MM-DD", and a day that exists
+pub(crate) fn check_date(date: &str) -> Result<()> {
+    let parts: Vec<&str> = date.split('-').collect();
+    let valid = match parts.as_slice() {
+        [y, m, d] if y.len() == 4 && m.len() == 2 && d.len() == 2 && all_digits(&parts) => {
+            let (year, month, day) = (y.parse().unwrap_or(0), m.parse().unwrap_or(0), d.parse().unwrap_or(0));
+            (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month)
+        }
+        _ => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(invalid(format!("Date must be a real day as YYYY-MM-DD, got '{}'", date)))
+    }
+}
+
+// "HH:MM", 24-hour. An end time before the start time means the dash ran
+// past midnight, so the two are not compared.
+pub(crate) fn check_time(field: &str, time: &Option<String>) -> Result<()> {
+    let Some(time) = time else { return Ok(()) };
+    let parts: Vec<&str> = time.split(':').collect();
+    let valid = match parts.as_slice() {
+        [h, m] if h.len() == 2 && m.len() == 2 && all_digits(&parts) => {
+            h.parse::<u32>().is_ok_and(|h| h < 24) && m.parse::<u32>().is_ok_and(|m| m < 60)
+        }
+        _ => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(invalid(format!("{} must be HH:MM (24-hour), got '{}'", field, time)))
+    }
+}
+
+fn check_amount(field: &str, amount: Option<Money>) -> Result<()> {
+    match amount {
+        Some(amount) if amount.is_negative() || amount > MAX_AMOUNT => Err(invalid(format!(
+            "{} must be between {} and {}",
+            field,
+            Money::ZERO,
+            MAX_AMOUNT
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn check_range(field: &str, value: Option<i64>, max: i64, unit: &str) -> Result<()> {
+    match value {
+        Some(value) if !(0..=max).contains(&value) => {
+            Err(invalid(format!("{} must be between 0 and {}{}", field, max, unit)))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Time on deliveries is part of the dash, so it cannot exceed it
+fn check_durations(active_time: Option<i64>, total_time: Option<i64>) -> Result<()> {
+    match (active_time, total_time) {
+        (Some(active), Some(total)) if active > total => {
+            Err(invalid("Active time cannot be longer than total time".into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Trimmed store name; blank counts as unknown
+fn clean_store(store: &Option<String>) -> Result<Option<String>> {
+    let Some(store) = store.as_deref().map(str::trim).filter(|store| !store.is_empty()) else {
+        return Ok(None);
+    };
+    if store.chars().count() > MAX_STORE_LEN {
+        return Err(invalid(format!("Store name must be at most {} characters", MAX_STORE_LEN)));
+    }
+    Ok(Some(store.to_string()))
+}
+
+impl SessionInsert {
+    fn validate(&self) -> Result<()> {
+        check_date(&self.date)?;
+        SessionUpdate::check_fields(
+            self.total_earnings,
+            self.base_pay,
+            self.tips,
+            [&self.start_time, &self.end_time],
+            [self.active_time, self.total_time, self.offers_count, self.deliveries],
+        )
+    }
+}
+
+impl SessionUpdate {
+    fn validate(&self) -> Result<()> {
+        if let Some(date) = &self.date {
+            check_date(date)?;
+        }
+        SessionUpdate::check_fields(
+            self.total_earnings,
+            self.base_pay,
+            self.tips,
+            [&self.start_time, &self.end_time],
+            [self.active_time, self.total_time, self.offers_count, self.deliveries],
+        )
+    }
+
+    // Rules shared by inserts and partial updates
+    fn check_fields(
+        total_earnings: Option<Money>,
+        base_pay: Option<Money>,
+        tips: Option<Money>,
+        [start_time, end_time]: [&Option<String>; 2],
+        [active_time, total_time, offers_count, deliveries]: [Option<i64>; 4],
+    ) -> Result<()> {
+        check_amount("Total earnings", total_earnings)?;
+        check_amount("Base pay", base_pay)?;
+        check_amount("Tips", tips)?;
+        check_time("Start time", start_time)?;
+        check_time("End time", end_time)?;
+        check_range("Active time", active_time, MAX_MINUTES, " minutes")?;
+        check_range("Total time", total_time, MAX_MINUTES, " minutes")?;
+        check_range("Offers", offers_count, MAX_COUNT, "")?;
+        check_range("Deliveries", deliveries, MAX_COUNT, "")?;
+        check_durations(active_time, total_time)
+    }
+}
+
+impl OfferInsert {
+    fn validate(&self) -> Result<()> {
+        clean_store(&self.store)?;
+        check_amount("Offer earnings", self.total_earnings)
+    }
+}
+
+impl OfferUpdate {
+    fn validate(&self) -> Result<()> {
+        clean_store(&self.store)?;
+        check_amount("Offer earnings", self.total_earnings)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Repository — the statements behind the commands. Writers take a
+// connection so a command can combine several in one transaction; inputs
+// are validated before they get here.
+// ---------------------------------------------------------------------------
+
+async fn insert_session(conn: &mut SqliteConnection, user: &CurrentUser, data: &SessionInsert) -> Result<i64> {
+    let key = user.data_key.as_ref();
+    let result = sqlx::query(
+        "INSERT INTO sessions
+           (user_id, date, total_earnings, base_pay, tips,
+            start_time, end_time, active_time, total_time,
+            offers_count, deliveries)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+    )
+    .bind(user.user_id)
+    .bind(&data.date)
+    .bind(seal_amount(key, data.total_earnings)?)
+    .bind(seal_amount(key, data.base_pay)?)
+    .bind(seal_amount(key, data.tips)?)
+    .bind(&data.start_time)
+    .bind(&data.end_time)
+    .bind(data.active_time)
+    .bind(data.total_time)
+    .bind(data.offers_count)
+    .bind(data.deliveries)
+    .execute(&mut *conn)
+    .await
+    .map_err(DashlensError::database("create session"))?;
+    Ok(result.last_insert_rowid())
+}
+
+// Overwrite every field of a stored session (unlike update_session, a null
+// clears the value)
+async fn replace_session(conn: &mut SqliteConnection, user: &CurrentUser, id: i64, data: &SessionInsert) -> Result<()> {
+    let key = user.data_key.as_ref();
+    let result = sqlx::query(
+        "UPDATE sessions SET
+           date = $1, total_earnings = $2, base_pay = $3, tips = $4,
+           start_time = $5, end_time = $6, active_time = $7, total_time = $8,
+           offers_count = $9, deliveries = $10
+         WHERE id = $11 AND user_id = $12",
+    )
+    .bind(&data.date)
+    .bind(seal_amount(key, data.total_earnings)?)
+    .bind(seal_amount(key, data.base_pay)?)
+    .bind(seal_amount(key, data.tips)?)
+    .bind(&data.start_time)
+    .bind(&data.end_time)
+    .bind(data.active_time)
+    .bind(data.total_time)
+    .bind(data.offers_count)
+    .bind(data.deliveries)
+    .bind(id)
+    .bind(user.user_id)
+    .execute(&mut *conn)
+    .await
+    .map_err(DashlensError::database("update session"))?;
+
+    if result.rows_affected() == 0 {
+        return Err(DashlensError::NotFound(format!("Session {} not found", id)));
+    }
+    Ok(())
+}
+
+async fn insert_offer(
+    conn: &mut SqliteConnection,
+    user: &CurrentUser,
+    session_id: i64,
+    data: &OfferInsert,
+) -> Result<i64> {
+    let result = sqlx::query("INSERT INTO offers (session_id, store, total_earnings) VALUES ($1, $2, $3)")
+        .bind(session_id)
+        .bind(clean_store(&data.store)?)
+        .bind(seal_amount(user.data_key.as_ref(), data.total_earnings)?)
+        .execute(&mut *conn)
+        .await
+        .map_err(DashlensError::database("create offer"))?;
+    Ok(result.last_insert_rowid())
+}
+
+async fn delete_offers(conn: &mut SqliteConnection, session_id: i64) -> Result<()> {
+    sqlx::query("DELETE FROM offers WHERE session_id = $1")
+        .bind(session_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(DashlensError::database("delete offers"))?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+async fn fetch_session(pool: &Pool<Sqlite>, user: &CurrentUser, id: i64) -> Result<Option<Session>> {
+    let sql = format!("SELECT {} FROM sessions WHERE id = $1 AND user_id = $2", SESSION_COLUMNS);
+    let row: Option<SessionRow> = sqlx::query_as(&sql)
+        .bind(id)
+        .bind(user.owner_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(DashlensError::database("load session"))?;
+    row.map(|row| row.open(user.data_key.as_ref())).transpose()
+}
+
+async fn fetch_offers(pool: &Pool<Sqlite>, user: &CurrentUser, session_id: i64) -> Result<Vec<Offer>> {
+    let sql = format!(
+        "SELECT {} FROM offers
+         WHERE session_id = $1
+           AND session_id IN (SELECT id FROM sessions WHERE user_id = $2)
+         ORDER BY id ASC",
+        OFFER_COLUMNS
+    );
+    let rows: Vec<OfferRow> = sqlx::query_as(&sql)
+        .bind(session_id)
+        .bind(user.owner_id)
+        .fetch_all(pool)
+        .await
+        .map_err(DashlensError::database("load offers"))?;
+    rows.into_iter().map(|row| row.open(user.data_key.as_ref())).collect()
+}
+
+// Fail unless the session exists and belongs to the user
+async fn require_session(conn: &mut SqliteConnection, user_id: i64, id: i64) -> Result<()> {
+    let found: Option<(i64,)> = sqlx::query_as("SELECT id FROM sessions WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(DashlensError::database("load session"))?;
+    match found {
+        Some(_) => Ok(()),
+        None => Err(DashlensError::NotFound(format!("Session {} not found", id))),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Session commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn create_session(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    data: SessionInsert,
+) -> Result<i64> {
+    user.require_owner()?;
+    data.validate()?;
+    let pool = crate::db::pool(&db).await?;
+    let mut tx = crate::db::begin_write(&pool).await?;
+
+    let id = insert_session(&mut tx, &user, &data).await?;
+    let details = serde_json::json!({ "session_id": id, "date": data.date });
+    audit::record(&mut tx, Some(user.user_id), &user.username, AuditEvent::SessionCreated, details).await?;
+    tx.commit().await.map_err(DashlensError::database("create session"))?;
+
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn list_sessions(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<Vec<Session>> {
+    let user_id = user.owner_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let sql = format!(
+        "SELECT {} FROM sessions WHERE user_id = $1 ORDER BY date DESC, start_time DESC",
+        SESSION_COLUMNS
+    );
+    let rows: Vec<SessionRow> = sqlx::query_as(&sql)
+        .bind(user_id)
+        .fetch_all(&pool)
+        .await
+        .map_err(DashlensError::database("list sessions"))?;
+    rows.into_iter().map(|row| row.open(user.data_key.as_ref())).collect()
+}
+
+#[tauri::command]
+pub async fn get_session(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    id: i64,
+) -> Result<Option<Session>> {
+    let pool = crate::db::pool(&db).await?;
+    fetch_session(&pool, &user, id).await
+}
+
+#[tauri::command]
+pub async fn get_session_with_offers(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    id: i64,
+) -> Result<Option<SessionWithOffers>> {
+    let pool = crate::db::pool(&db).await?;
+
+    let Some(session) = fetch_session(&pool, &user, id).await? else {
+        return Ok(None);
+    };
+    let offers = fetch_offers(&pool, &user, id).await?;
+    Ok(Some(SessionWithOffers { session, offers }))
+}
+
+#[tauri::command]
+pub async fn update_session(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    id: i64,
+    data: SessionUpdate,
+) -> Result<()> {
+    user.require_owner()?;
+    data.validate()?;
+    let user_id = user.user_id;
+    let key = user.data_key.as_ref();
+    let pool = crate::db::pool(&db).await?;
+    let mut tx = crate::db::begin_write(&pool).await?;
+
+    let result = sqlx::query(
+        "UPDATE sessions SET
+           date           = COALESCE($1,  date),
+           total_earnings = COALESCE($2,  total_earnings),
+           base_pay       = COALESCE($3,  base_pay),
+           tips           = COALESCE($4,  tips),
+           start_time     = COALESCE($5,  start_time),
+           end_time       = COALESCE($6,  end_time),
+           active_time    = COALESCE($7,  active_time),
+           total_time     = COALESCE($8,  total_time),
+           offers_count   = COALESCE($9,  offers_count),
+           deliveries     = COALESCE($10, deliveries)
+         WHERE id = $11 AND user_id = $12",
+    )
+    .bind(&data.date)
+    .bind(seal_amount(key, data.total_earnings)?)
+    .bind(seal_amount(key, data.base_pay)?)
+    .bind(seal_amount(key, data.tips)?)
+    .bind(&data.start_time)
+    .bind(&data.end_time)
+    .bind(data.active_time)
+    .bind(data.total_time)
+    .bind(data.offers_count)
+    .bind(data.deliveries)
+    .bind(id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(DashlensError::database("update session"))?;
+
+    if result.rows_affected() == 0 {
+        return Err(DashlensError::NotFound(format!("Session {} not found", id)));
+    }
+    // A partial update can break a rule together with a stored value; the
+    // transaction rolls back when this returns early
+    let (active_time, total_time): (Option<i64>, Option<i64>) =
+        sqlx::query_as("SELECT active_time, total_time FROM sessions WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DashlensError::database("load session"))?;
+    check_durations(active_time, total_time)?;
+
+    let details = serde_json::json!({ "session_id": id });
+    audit::record(&mut tx, Some(user_id), &user.username, AuditEvent::SessionUpdated, details).await?;
+    tx.commit().await.map_err(DashlensError::database("update session"))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_session(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    id: i64,
+) -> Result<()> {
+    user.require_owner()?;
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+    let mut tx = crate::db::begin_write(&pool).await?;
+
+    // Offers are deleted via ON DELETE CASCADE in the schema
+    let result = sqlx::query("DELETE FROM sessions WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("delete session"))?;
+
+    if result.rows_affected() == 0 {
+        return Err(DashlensError::NotFound(format!("Session {} not found", id)));
+    }
+    let details = serde_json::json!({ "session_id": id });
+    audit::record(&mut tx, Some(user_id), &user.username, AuditEvent::SessionDeleted, details).await?;
+    tx.commit().await.map_err(DashlensError::database("delete session"))?;
+    Ok(())
+}
+
+// Save a session together with its offers in one transaction: a new session
+// when `session_id` is absent, otherwise the stored session is overwritten
+// and its offers replaced. Returns the session id; on any failure nothing
+// is written.
+#[tauri::command]
+pub async fn save_session_with_offers(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    session_id: Option<i64>,
+    session: SessionInsert,
+    offers: Vec<OfferInsert>,
+) -> Result<i64> {
+    user.require_owner()?;
+    session.validate()?;
+    offers.iter().try_for_each(OfferInsert::validate)?;
+    let pool = crate::db::pool(&db).await?;
+    save_with_offers(&pool, &user, session_id, &session, &offers).await
+}
+
+async fn save_with_offers(
+    pool: &Pool<Sqlite>,
+    user: &CurrentUser,
+    session_id: Option<i64>,
+    session: &SessionInsert,
+    offers: &[OfferInsert],
+) -> Result<i64> {
+    let mut tx = crate::db::begin_write(pool).await?;
+
+    let (id, event) = match session_id {
+        Some(id) => {
+            replace_session(&mut tx, user, id, session).await?;
+            delete_offers(&mut tx, id).await?;
+            (id, AuditEvent::SessionUpdated)
+        }
+        None => (insert_session(&mut tx, user, session).await?, AuditEvent::SessionCreated),
+    };
+    for offer in offers {
+        insert_offer(&mut tx, user, id, offer).await?;
+    }
+
+    let details = serde_json::json!({ "session_id": id, "date": session.date, "offers": offers.len() });
+    audit::record(&mut tx, Some(user.user_id), &user.username, event, details).await?;
+    tx.commit().await.map_err(DashlensError::database("save session"))?;
+    Ok(id)
+}
+
+// ---------------------------------------------------------------------------
+// Offer commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn create_offer(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    session_id: i64,
+    data: OfferInsert,
+) -> Result<i64> {
+    user.require_owner()?;
+    data.validate()?;
+    let pool = crate::db::pool(&db).await?;
+    let mut conn = pool.acquire().await.map_err(DashlensError::database("open connection"))?;
+    require_session(&mut conn, user.user_id, session_id).await?;
+    insert_offer(&mut conn, &user, session_id, &data).await
+}
+
+// Bulk-insert multiple offers for a session (used when saving OCR results).
+// All or none are inserted.
+#[tauri::command]
+pub async fn create_offers(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    session_id: i64,
+    offers: Vec<OfferInsert>,
+) -> Result<()> {
+    user.require_owner()?;
+    offers.iter().try_for_each(OfferInsert::validate)?;
+    let pool = crate::db::pool(&db).await?;
+    let mut tx = crate::db::begin_write(&pool).await?;
+    require_session(&mut tx, user.user_id, session_id).await?;
+
+    for offer in &offers {
+        insert_offer(&mut tx, &user, session_id, offer).await?;
+    }
+    tx.commit().await.map_err(DashlensError::database("create offers"))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_offers(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    session_id: i64,
+) -> Result<Vec<Offer>> {
+    let pool = crate::db::pool(&db).await?;
+    fetch_offers(&pool, &user, session_id).await
+}
+
+#[tauri::command]
+pub async fn update_offer(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    id: i64,
+    data: OfferUpdate,
+) -> Result<()> {
+    user.require_owner()?;
+    data.validate()?;
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let result = sqlx::query(
+        "UPDATE offers SET
+           store          = COALESCE($1, store),
+           total_earnings = COALESCE($2, total_earnings)
+         WHERE id = $3
+           AND session_id IN (SELECT id FROM sessions WHERE user_id = $4)",
+    )
+    .bind(clean_store(&data.store)?)
+    .bind(seal_amount(user.data_key.as_ref(), data.total_earnings)?)
+    .bind(id)
+    .bind(user_id)
+    .execute(&pool)
+    .await
+    .map_err(DashlensError::database("update offer"))?;
+
+    if result.rows_affected() == 0 {
+        return Err(DashlensError::NotFound(format!("Offer {} not found", id)));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_offer(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    id: i64,
+) -> Result<()> {
+    user.require_owner()?;
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let result = sqlx::query(
+        "DELETE FROM offers
+         WHERE id = $1
+           AND session_id IN (SELECT id FROM sessions WHERE user_id = $2)",
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(&pool)
+    .await
+    .map_err(DashlensError::database("delete offer"))?;
+
+    if result.rows_affected() == 0 {
+        return Err(DashlensError::NotFound(format!("Offer {} not found", id)));
+    }
+    Ok(())
+}
+
+// Delete all offers for a session — used when re-saving after edits
+#[tauri::command]
+pub async fn delete_session_offers(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    session_id: i64,
+) -> Result<()> {
+    user.require_owner()?;
+    let pool = crate::db::pool(&db).await?;
+    let mut conn = pool.acquire().await.map_err(DashlensError::database("open connection"))?;
+    require_session(&mut conn, user.user_id, session_id).await?;
+    delete_offers(&mut conn, session_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roles::Role;
+
+    fn text(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn dates_must_be_real_days() {
+        for date in ["2024-01-31", "2024-02-29", "2000-02-29", "2023-12-01"] {
+            assert!(check_date(date).is_ok(), "{}", date);
+        }
+        // 1900 and 2100 are not leap years; 2000 is
+        for date in ["2023-02-29", "1900-02-29", "2100-02-29", "2024-02-30", "2024-04-31", "2024-13-01", "2024-00-10"] {
+            assert!(check_date(date).is_err(), "{}", date);
+        }
+        for date in ["2024-1-05", "24-01-05", "2024/01/05", "2024-01-05T00:00", "2024-01-+5", ""] {
+            assert!(check_date(date).is_err(), "{}", date);
+        }
+    }
+
+    #[test]
+    fn times_are_24_hour() {
+        assert!(check_time("Start time", &None).is_ok());
+        for value in ["00:00", "09:30", "23:59"] {
+            assert!(check_time("Start time", &text(value)).is_ok(), "{}", value);
+        }
+        for value in ["24:00", "12:60", "9:30", "09:3", "0930", "09:30:00", "+9:30", ""] {
+            assert!(check_time("Start time", &text(value)).is_err(), "{}", value);
+        }
+        let error = check_time("End time", &text("25:00")).unwrap_err();
+        assert_eq!(error.to_string(), "End time must be HH:MM (24-hour), got '25:00'");
+    }
+
+    #[test]
+    fn amounts_stay_in_range() {
+        assert!(check_amount("Tips", None).is_ok());
+        assert!(check_amount("Tips", Some(Money::ZERO)).is_ok());
+        assert!(check_amount("Tips", Some(MAX_AMOUNT)).is_ok());
+        assert!(check_amount("Tips", Some(Money::from_cents(-1))).is_err());
+        assert!(check_amount("Tips", Some(Money::from_cents(MAX_AMOUNT.cents() + 1))).is_err());
+        let error = check_amount("Tips", Some(Money::from_cents(-50))).unwrap_err();
+        assert_eq!(error.to_string(), "Tips must be between $0.00 and $100,000.00");
+    }
+
+    #[test]
+    fn active_time_fits_in_total_time() {
+        assert!(check_durations(Some(30), Some(45)).is_ok());
+        assert!(check_durations(Some(45), Some(45)).is_ok());
+        assert!(check_durations(Some(46), Some(45)).is_err());
+        assert!(check_durations(Some(46), None).is_ok());
+        assert!(check_durations(None, Some(45)).is_ok());
+    }
+
+    #[test]
+    fn store_names_are_trimmed() {
+        assert_eq!(clean_store(&None).unwrap(), None);
+        assert_eq!(clean_store(&text("   ")).unwrap(), None);
+        assert_eq!(clean_store(&text("  Taco Spot ")).unwrap(), text("Taco Spot"));
+        assert!(clean_store(&Some("é".repeat(MAX_STORE_LEN))).is_ok());
+        assert!(clean_store(&Some("é".repeat(MAX_STORE_LEN + 1))).is_err());
+    }
+
+    // A failed offer insert part-way through leaves no trace: no new
+    // session, and an edited session keeps its fields and old offers
+    #[test]
+    fn saving_with_offers_is_all_or_nothing() {
+        tauri::async_runtime::block_on(async {
+            let pool = crate::db::memory_pool().await;
+            sqlx::raw_sql(
+                "INSERT INTO users (id, username, password_hash) VALUES (1, 'driver', 'x');
+                 CREATE TRIGGER reject_offer BEFORE INSERT ON offers WHEN NEW.store = 'Broken'
+                 BEGIN SELECT RAISE(ABORT, 'offer rejected'); END;",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            let user = CurrentUser {
+                user_id: 1,
+                username: "driver".into(),
+                role: Role::Owner,
+                owner_id: 1,
+                data_key: None,
+            };
+            let session = |date: &str| SessionInsert {
+                date: date.into(),
+                total_earnings: Some(Money::from_cents(2_500)),
+                base_pay: None,
+                tips: None,
+                start_time: None,
+                end_time: None,
+                active_time: None,
+                total_time: None,
+                offers_count: None,
+                deliveries: None,
+            };
+            let offer = |store: &str| OfferInsert {
+                store: Some(store.into()),
+                total_earnings: Some(Money::from_cents(800)),
+            };
+            let snapshot = |pool: Pool<Sqlite>| async move {
+                let sessions: Vec<(i64, String)> = sqlx::query_as("SELECT id, date FROM sessions ORDER BY id")
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+                let offers: Vec<(i64, String)> = sqlx::query_as("SELECT session_id, store FROM offers ORDER BY id")
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+                let (audited,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM audit_log")
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+                (sessions, offers, audited)
+            };
+
+            let offers = [offer("Taco Spot"), offer("Broken")];
+            let failed = save_with_offers(&pool, &user, None, &session("2024-03-01"), &offers).await;
+            assert!(failed.is_err());
+            assert_eq!(snapshot(pool.clone()).await, (vec![], vec![], 0));
+
+            let offers = [offer("Taco Spot"), offer("Noodle House")];
+            let id = save_with_offers(&pool, &user, None, &session("2024-03-01"), &offers).await.unwrap();
+            let saved = snapshot(pool.clone()).await;
+            assert_eq!(saved.0, [(id, "2024-03-01".to_string())]);
+            assert_eq!(saved.1.len(), 2);
+
+            let offers = [offer("Burger Barn"), offer("Broken")];
+            let failed = save_with_offers(&pool, &user, Some(id), &session("2024-03-02"), &offers).await;
+            assert!(failed.is_err());
+            assert_eq!(snapshot(pool.clone()).await, saved);
+        });
+    }
+
+    // Reads go by owner_id: a viewer sees the owner's sessions and offers,
+    // never another account's
+    #[test]
+    fn viewers_read_through_their_owner() {
+        tauri::async_runtime::block_on(async {
+            let pool = crate::db::memory_pool().await;
+            sqlx::raw_sql(
+                "INSERT INTO users (id, username, password_hash, role, owner_id) VALUES
+                     (1, 'owner', 'x', 'owner', NULL),
+                     (2, 'partner', 'x', 'viewer', 1),
+                     (3, 'stranger', 'x', 'owner', NULL);
+                 INSERT INTO sessions (id, user_id, date, total_earnings) VALUES
+                     (10, 1, '2024-03-01', 2500), (11, 3, '2024-03-02', 4000);
+                 INSERT INTO offers (session_id, store, total_earnings) VALUES
+                     (10, 'Taco Spot', 800), (11, 'Noodle House', 900);",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            let (role, owner_id) = crate::roles::lookup(&pool, 2).await.unwrap();
+            let viewer = CurrentUser {
+                user_id: 2,
+                username: "partner".into(),
+                role,
+                owner_id,
+                data_key: None,
+            };
+
+            let session = fetch_session(&pool, &viewer, 10).await.unwrap().unwrap();
+            assert_eq!(session.total_earnings, Some(Money::from_cents(2_500)));
+            let offers = fetch_offers(&pool, &viewer, 10).await.unwrap();
+            assert_eq!(offers.len(), 1);
+            assert_eq!(offers[0].store.as_deref(), Some("Taco Spot"));
+
+            assert!(fetch_session(&pool, &viewer, 11).await.unwrap().is_none());
+            assert!(fetch_offers(&pool, &viewer, 11).await.unwrap().is_empty());
+        });
+    }
+}
@@ -0,0 +1,191 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+
+use crate::password_policy::PasswordFeedback;
+
+// ---------------------------------------------------------------------------
+// Error type shared by every command.
+//
+// Serializes to `{ code, message, details }` so the frontend can branch on
+// `code` instead of matching message text. `message` is safe to show to the
+// user; `details` carries structured extras (e.g. retry_after_secs) or the
+// underlying cause for logging.
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub enum DashlensError {
+    Unauthenticated(String),
+    InvalidCredentials(String),
+    Locked { retry_after_secs: i64 },
+    TotpRequired,
+    PinRequired { username: String },
+    PermissionDenied(String),
+    WeakPassword(Box<PasswordFeedback>),
+    Validation(String),
+    Conflict(String),
+    NotFound(String),
+    Database { message: String, cause: String },
+    #[allow(dead_code)] // raised once OCR parsing moves into Rust
+    Ocr(String),
+    Io { message: String, cause: String },
+    Internal(String),
+}
+
+pub type Result<T> = std::result::Result<T, DashlensError>;
+
+impl DashlensError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            DashlensError::Unauthenticated(_) => "unauthenticated",
+            DashlensError::InvalidCredentials(_) => "invalid_credentials",
+            DashlensError::Locked { .. } => "locked",
+            DashlensError::TotpRequired => "totp_required",
+            DashlensError::PinRequired { .. } => "pin_required",
+            DashlensError::PermissionDenied(_) => "permission_denied",
+            DashlensError::WeakPassword(_) => "weak_password",
+            DashlensError::Validation(_) => "validation",
+            DashlensError::Conflict(_) => "conflict",
+            DashlensError::NotFound(_) => "not_found",
+            DashlensError::Database { .. } => "database",
+            DashlensError::Ocr(_) => "ocr",
+            DashlensError::Io { .. } => "io",
+            DashlensError::Internal(_) => "internal",
+        }
+    }
+
+    fn details(&self) -> Option<Value> {
+        match self {
+            DashlensError::Locked { retry_after_secs } => Some(json!({ "retry_after_secs": retry_after_secs })),
+            DashlensError::PinRequired { username } => Some(json!({ "username": username })),
+            DashlensError::WeakPassword(feedback) => Some(json!(feedback)),
+            DashlensError::Database { cause, .. } | DashlensError::Io { cause, .. } => Some(json!({ "cause": cause })),
+            _ => None,
+        }
+    }
+
+    // `.map_err(DashlensError::database("load session"))` — keeps the action
+    // in the message and the driver error in details
+    pub fn database(action: &'static str) -> impl Fn(sqlx::Error) -> DashlensError {
+        move |e| match e {
+            sqlx::Error::RowNotFound => DashlensError::NotFound(format!("Failed to {}: not found", action)),
+            e => DashlensError::Database {
+                message: format!("Failed to {}", action),
+                cause: e.to_string(),
+            },
+        }
+    }
+
+    pub fn io(action: &'static str) -> impl Fn(std::io::Error) -> DashlensError {
+        move |e| DashlensError::Io {
+            message: format!("Failed to {}", action),
+            cause: e.to_string(),
+        }
+    }
+
+    pub fn not_logged_in() -> Self {
+        DashlensError::Unauthenticated("Not logged in".into())
+    }
+}
+
+impl fmt::Display for DashlensError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DashlensError::Unauthenticated(message)
+            | DashlensError::InvalidCredentials(message)
+            | DashlensError::PermissionDenied(message)
+            | DashlensError::Validation(message)
+            | DashlensError::Conflict(message)
+            | DashlensError::NotFound(message)
+            | DashlensError::Ocr(message)
+            | DashlensError::Internal(message)
+            | DashlensError::Database { message, .. }
+            | DashlensError::Io { message, .. } => f.write_str(message),
+            DashlensError::Locked { retry_after_secs } => write!(
+                f,
+                "Too many failed attempts. Try again in {}.",
+                crate::throttle::describe_wait(*retry_after_secs)
+            ),
+            DashlensError::TotpRequired => f.write_str("Enter the code from your authenticator app"),
+            DashlensError::PinRequired { .. } => f.write_str("Enter your PIN"),
+            DashlensError::WeakPassword(feedback) => {
+                f.write_str(feedback.warning.as_deref().unwrap_or("Choose a stronger password"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for DashlensError {}
+
+impl Serialize for DashlensError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DashlensError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<sqlx::Error> for DashlensError {
+    fn from(e: sqlx::Error) -> Self {
+        DashlensError::database("access the database")(e)
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for DashlensError {
+    fn from(_: std::sync::PoisonError<T>) -> Self {
+        DashlensError::Internal("Internal state is unavailable after an earlier failure".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wire(error: DashlensError) -> Value {
+        serde_json::to_value(&error).unwrap()
+    }
+
+    // The shape src/types/auth.ts (DashlensError) expects
+    #[test]
+    fn serializes_code_message_and_details() {
+        assert_eq!(
+            wire(DashlensError::Locked { retry_after_secs: 90 }),
+            json!({
+                "code": "locked",
+                "message": "Too many failed attempts. Try again in 2 minutes.",
+                "details": { "retry_after_secs": 90 },
+            })
+        );
+        assert_eq!(
+            wire(DashlensError::Conflict("Username already exists".into())),
+            json!({ "code": "conflict", "message": "Username already exists", "details": null })
+        );
+        assert_eq!(
+            wire(DashlensError::PermissionDenied("Viewers can look at earnings but not change them".into())),
+            json!({
+                "code": "permission_denied",
+                "message": "Viewers can look at earnings but not change them",
+                "details": null,
+            })
+        );
+        assert_eq!(
+            wire(DashlensError::PinRequired { username: "driver".into() }),
+            json!({ "code": "pin_required", "message": "Enter your PIN", "details": { "username": "driver" } })
+        );
+    }
+
+    // The message is for the user; the driver error goes in details
+    #[test]
+    fn database_errors_keep_the_cause_out_of_the_message() {
+        let error = DashlensError::database("load session")(sqlx::Error::PoolTimedOut);
+        let value = wire(error);
+        assert_eq!(value["code"], "database");
+        assert_eq!(value["message"], "Failed to load session");
+        assert!(value["details"]["cause"].as_str().is_some_and(|cause| !cause.is_empty()));
+
+        let missing = wire(DashlensError::database("load session")(sqlx::Error::RowNotFound));
+        assert_eq!(missing["code"], "not_found");
+    }
+}
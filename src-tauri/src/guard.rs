@@ -0,0 +1,88 @@
+use tauri::ipc::{CommandArg, CommandItem, Invoke, InvokeError};
+use tauri::Runtime;
+
+use crate::auth::AuthState;
+use crate::error::DashlensError;
+use crate::roles::Role;
+use crate::vault::DataKey;
+
+// ---------------------------------------------------------------------------
+// Authorization for app commands.
+//
+// Two layers:
+//   * `authorize` wraps the invoke handler in lib.rs and rejects every
+//     command not in PUBLIC_COMMANDS unless a live session exists, so a new
+//     command is protected by default.
+//   * `CurrentUser` is a command argument that resolves the logged-in user
+//     (or fails the call); data commands take it instead of reading
+//     AuthState themselves, which also gives them the id to scope queries.
+// ---------------------------------------------------------------------------
+
+// Commands callable without a session: logging in, registering, restoring or
+// PIN-unlocking a remembered login, recovering a forgotten password, the
+// login screen's profile picker, the password strength meter and status
+// checks.
+pub const PUBLIC_COMMANDS: &[&str] = &[
+    "register",
+    "login",
+    "restore_session",
+    "recover_account",
+    "unlock_with_pin",
+    "clear_session",
+    "get_current_user",
+    "check_auth_status",
+    "list_recent_profiles",
+    "check_password_strength",
+];
+
+// The logged-in user, resolved from AuthState when the command is invoked
+#[derive(Debug, Clone)]
+pub struct CurrentUser {
+    pub user_id: i64,
+    pub username: String,
+    pub role: Role,
+    pub owner_id: i64,             // whose data the user reads; their own for owners
+    pub data_key: Option<DataKey>, // set when the account's earnings are encrypted
+}
+
+impl CurrentUser {
+    // Fail unless the user may change data (viewers are read-only)
+    pub fn require_owner(&self) -> Result<(), DashlensError> {
+        match self.role {
+            Role::Owner => Ok(()),
+            Role::Viewer => Err(DashlensError::PermissionDenied(
+                "Viewers can look at earnings but not change them".into(),
+            )),
+        }
+    }
+}
+
+impl<'de, R: Runtime> CommandArg<'de, R> for CurrentUser {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        let state = command
+            .message
+            .state_ref()
+            .try_get::<AuthState>()
+            .ok_or_else(|| InvokeError::from(DashlensError::Internal("AuthState is not managed".into())))?;
+        let session = state.touch().map_err(InvokeError::from)?;
+        let data_key = state.data_key().map_err(InvokeError::from)?;
+        Ok(CurrentUser {
+            user_id: session.user_id,
+            username: session.username,
+            role: session.role,
+            owner_id: session.owner_id,
+            data_key,
+        })
+    }
+}
+
+// Whether the invoked command may run: public, or a live session exists
+pub fn authorize<R: Runtime>(invoke: &Invoke<R>) -> bool {
+    let command = invoke.message.command();
+    PUBLIC_COMMANDS.contains(&command)
+        || invoke
+            .message
+            .state_ref()
+            .try_get::<AuthState>()
+            .is_some_and(|state| matches!(state.active_session(), Ok(Some(_))))
+}
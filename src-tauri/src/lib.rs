@@ -1,50 +1,144 @@
+mod audit;
 mod auth;
+mod db;
+mod entries;
+mod error;
+mod guard;
+mod money;
+mod password;
+mod password_policy;
+mod profiles;
+mod recovery;
+mod remember;
+mod roles;
+mod secret;
+mod session_query;
+mod settings;
+mod throttle;
+mod totp;
+mod username;
+mod vault;
 
+use audit::{
+    list_audit_log,
+    verify_audit_log,
+    record_export
+};
 use auth::{
     AuthState, 
-    hash_password, 
-    verify_password, 
-    set_session,
+    set_pin,
+    remove_pin,
+    pin_status,
+    unlock_with_pin,
+    register,
+    login,
+    switch_user,
+    restore_session,
+    change_password,
+    delete_account,
+    get_argon2_policy,
+    calibrate_argon2,
     clear_session,
     get_current_user, 
-    check_auth_status
+    check_auth_status,
+    touch_session,
+    set_idle_timeout,
+    spawn_lock_watcher
+};
+use entries::{
+    create_session,
+    list_sessions,
+    get_session,
+    get_session_with_offers,
+    update_session,
+    delete_session,
+    save_session_with_offers,
+    create_offer,
+    create_offers,
+    list_offers,
+    update_offer,
+    delete_offer,
+    delete_session_offers
+};
+use password_policy::check_password_strength;
+use session_query::{
+    query_sessions,
+    query_all_sessions
+};
+use profiles::{
+    list_recent_profiles,
+    forget_recent_profile
+};
+use roles::{
+    create_viewer,
+    list_viewers,
+    remove_viewer
+};
+use recovery::{
+    recover_account,
+    regenerate_recovery_codes,
+    recovery_codes_remaining
+};
+use totp::{
+    begin_totp_enrollment,
+    confirm_totp_enrollment,
+    disable_totp,
+    totp_status
+};
+use username::list_username_conflicts;
+use vault::{
+    enable_encryption,
+    disable_encryption,
+    encryption_status
 };
+use tauri::{ipc::Invoke, Manager, Runtime};
 use tauri_plugin_sql::{Builder, Migration, MigrationKind};
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(
-            Builder::default()
-                .add_migrations(
-                    "sqlite:dashlens.db",
-                    vec![
-                        // -------------------------------------------------------
-                        // v1 — users table (original)
-                        // -------------------------------------------------------
-                        Migration {
-                            version: 1,
-                            description: "create_users_table",
-                            sql: "CREATE TABLE IF NOT EXISTS users (
+// Deny-by-default wrapper around the generated invoke handler: anything not
+// in guard::PUBLIC_COMMANDS is rejected unless a user is logged in.
+fn guarded<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        if !guard::authorize(&invoke) {
+            invoke.resolver.reject(error::DashlensError::not_logged_in());
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+// Schema history, applied in order by the SQL plugin when the pool is
+// preloaded (see db.rs)
+fn migrations() -> Vec<Migration> {
+    vec![
+        // -------------------------------------------------------
+        // v1 — users table (original)
+        // -------------------------------------------------------
+        Migration {
+            version: 1,
+            description: "create_users_table",
+            sql: "CREATE TABLE IF NOT EXISTS users (
                                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                                 username TEXT NOT NULL UNIQUE,
                                 password_hash TEXT NOT NULL,
                                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP
                             )",
-                            kind: MigrationKind::Up,
-                        },
-                        // -------------------------------------------------------
-                        // v2 — earnings tables: weeks, days, offers
-                        //
-                        // Duration fields (active_time, total_time) are stored as
-                        // INTEGER minutes for easy arithmetic in queries.
-                        // Earnings fields are stored as REAL (dollars).
-                        // Time-of-day fields use TEXT "HH:MM" (24h).
-                        // -------------------------------------------------------
-                        Migration {
-                            version: 2,
-                            description: "create_earnings_tables",
-                            sql: "
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v2 — earnings tables: weeks, days, offers
+        //
+        // Duration fields (active_time, total_time) are stored as
+        // INTEGER minutes for easy arithmetic in queries.
+        // Earnings fields are stored as REAL (dollars); INTEGER
+        // cents since v13.
+        // Time-of-day fields use TEXT "HH:MM" (24h).
+        // -------------------------------------------------------
+        Migration {
+            version: 2,
+            description: "create_earnings_tables",
+            sql: "
                                 CREATE TABLE IF NOT EXISTS sessions (
                                     id             INTEGER PRIMARY KEY AUTOINCREMENT,
                                     date           TEXT    NOT NULL,
@@ -71,23 +165,453 @@ pub fn run() {
                                 CREATE INDEX IF NOT EXISTS idx_sessions_date     ON sessions(date);
                                 CREATE INDEX IF NOT EXISTS idx_offers_session_id ON offers(session_id);
                             ",
-                            kind: MigrationKind::Up,
-                        },
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v3 — per-user ownership of sessions
+        //
+        // Offers inherit ownership through sessions.session_id.
+        // Existing rows are assigned to the first registered
+        // user; if no user exists yet they stay NULL and are
+        // claimed by the first registration (see auth::register).
+        // -------------------------------------------------------
+        Migration {
+            version: 3,
+            description: "add_sessions_user_id",
+            sql: "
+                                ALTER TABLE sessions ADD COLUMN user_id INTEGER REFERENCES users(id) ON DELETE CASCADE;
+
+                                UPDATE sessions
+                                   SET user_id = (SELECT MIN(id) FROM users)
+                                 WHERE user_id IS NULL;
+
+                                CREATE INDEX IF NOT EXISTS idx_sessions_user_date ON sessions(user_id, date);
+                            ",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v4 — failed-login tracking for throttling / lockout
+        //
+        // Keyed by the submitted username (not users.id) so that
+        // attempts against unknown accounts are throttled too.
+        // Timestamps are INTEGER unix seconds.
+        // -------------------------------------------------------
+        Migration {
+            version: 4,
+            description: "create_login_attempts_table",
+            sql: "CREATE TABLE IF NOT EXISTS login_attempts (
+                                username       TEXT    PRIMARY KEY,
+                                failed_count   INTEGER NOT NULL DEFAULT 0,
+                                last_failed_at INTEGER,
+                                locked_until   INTEGER
+                            )",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v5 — remember-me tokens and key/value app settings
+        //
+        // auth_tokens stores only the SHA-256 of each token.
+        // Timestamps are INTEGER unix seconds.
+        // -------------------------------------------------------
+        Migration {
+            version: 5,
+            description: "create_auth_tokens_and_settings",
+            sql: "
+                                CREATE TABLE IF NOT EXISTS auth_tokens (
+                                    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                                    user_id     INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                                    token_hash  TEXT    NOT NULL UNIQUE,
+                                    created_at  INTEGER NOT NULL,
+                                    expires_at  INTEGER NOT NULL
+                                );
+
+                                CREATE TABLE IF NOT EXISTS app_settings (
+                                    key   TEXT PRIMARY KEY,
+                                    value TEXT NOT NULL
+                                );
+
+                                CREATE INDEX IF NOT EXISTS idx_auth_tokens_user_id ON auth_tokens(user_id);
+                            ",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v6 — optional TOTP second factor
+        //
+        // secret is base32. enabled stays 0 until enrollment is
+        // confirmed with a valid code. last_used_step is the most
+        // recent accepted 30s time step (replay protection).
+        // -------------------------------------------------------
+        Migration {
+            version: 6,
+            description: "create_user_totp_table",
+            sql: "CREATE TABLE IF NOT EXISTS user_totp (
+                                user_id        INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+                                secret         TEXT    NOT NULL,
+                                enabled        INTEGER NOT NULL DEFAULT 0,
+                                last_used_step INTEGER,
+                                created_at     INTEGER NOT NULL
+                            )",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v7 — single-use account recovery codes
+        //
+        // code_hash is an Argon2 PHC string; used_at (unix seconds)
+        // is set when the code is consumed.
+        // -------------------------------------------------------
+        Migration {
+            version: 7,
+            description: "create_recovery_codes_table",
+            sql: "
+                                CREATE TABLE IF NOT EXISTS recovery_codes (
+                                    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+                                    user_id    INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                                    code_hash  TEXT    NOT NULL,
+                                    created_at INTEGER NOT NULL,
+                                    used_at    INTEGER
+                                );
+
+                                CREATE INDEX IF NOT EXISTS idx_recovery_codes_user_id ON recovery_codes(user_id);
+                            ",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v8 — hash-chained audit log
+        //
+        // Append-only: the triggers reject UPDATE and DELETE. Each
+        // row's prev_hash is the previous row's hash (see audit.rs);
+        // the unique index keeps the chain from forking. user_id
+        // has no foreign key so entries outlive the account.
+        // -------------------------------------------------------
+        Migration {
+            version: 8,
+            description: "create_audit_log_table",
+            sql: "
+                                CREATE TABLE IF NOT EXISTS audit_log (
+                                    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+                                    created_at INTEGER NOT NULL,
+                                    user_id    INTEGER,
+                                    username   TEXT    NOT NULL,
+                                    event      TEXT    NOT NULL,
+                                    details    TEXT    NOT NULL DEFAULT '{}',
+                                    prev_hash  TEXT    NOT NULL,
+                                    hash       TEXT    NOT NULL
+                                );
+
+                                CREATE UNIQUE INDEX IF NOT EXISTS idx_audit_log_prev_hash ON audit_log(prev_hash);
+                                CREATE INDEX IF NOT EXISTS idx_audit_log_user_id ON audit_log(user_id);
+
+                                CREATE TRIGGER IF NOT EXISTS audit_log_no_update
+                                BEFORE UPDATE ON audit_log
+                                BEGIN
+                                    SELECT RAISE(ABORT, 'audit_log is append-only');
+                                END;
+
+                                CREATE TRIGGER IF NOT EXISTS audit_log_no_delete
+                                BEFORE DELETE ON audit_log
+                                BEGIN
+                                    SELECT RAISE(ABORT, 'audit_log is append-only');
+                                END;
+                            ",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v9 — optional at-rest encryption of earnings
+        //
+        // user_keys.wrapped_key is the user's data key wrapped
+        // under their password (JSON, see vault.rs); a row exists
+        // only while encryption is on. recovery_codes.wrapped_key
+        // holds the same key wrapped under each code.
+        // -------------------------------------------------------
+        Migration {
+            version: 9,
+            description: "create_user_keys_table",
+            sql: "
+                                CREATE TABLE IF NOT EXISTS user_keys (
+                                    user_id     INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+                                    wrapped_key TEXT    NOT NULL,
+                                    created_at  INTEGER NOT NULL
+                                );
+
+                                ALTER TABLE recovery_codes ADD COLUMN wrapped_key TEXT;
+                            ",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v10 — case-insensitive unique usernames
+        //
+        // Only the bookkeeping table: SQLite's LOWER() and
+        // NOCASE fold ASCII alone, so existing names are
+        // canonicalized (NFKC + Unicode lowercase) and the
+        // unique index created by username::canonicalize_existing
+        // at startup. Accounts renamed there are recorded in
+        // username_conflicts for the UI to report. Throttling
+        // state is keyed by the old spellings, so it is reset.
+        // -------------------------------------------------------
+        Migration {
+            version: 10,
+            description: "normalize_usernames",
+            sql: "
+                                CREATE TABLE IF NOT EXISTS username_conflicts (
+                                    id                INTEGER PRIMARY KEY AUTOINCREMENT,
+                                    user_id           INTEGER NOT NULL,
+                                    original_username TEXT    NOT NULL,
+                                    renamed_to        TEXT    NOT NULL,
+                                    conflicts_with    INTEGER NOT NULL,
+                                    detected_at       INTEGER NOT NULL
+                                );
+
+                                DELETE FROM login_attempts;
+                            ",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v11 — optional quick-unlock PIN
+        //
+        // pin_hash is an Argon2 PHC string (NULL = no PIN);
+        // pin_failed_count is reset by a password login.
+        // -------------------------------------------------------
+        Migration {
+            version: 11,
+            description: "add_users_pin",
+            sql: "
+                                ALTER TABLE users ADD COLUMN pin_hash TEXT;
+                                ALTER TABLE users ADD COLUMN pin_failed_count INTEGER NOT NULL DEFAULT 0;
+                            ",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v12 — owner / viewer roles (roles.rs). Existing
+        // accounts become owners; viewers point at the owner
+        // whose data they may read.
+        // -------------------------------------------------------
+        Migration {
+            version: 12,
+            description: "add_users_role",
+            sql: "
+                                ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'owner'
+                                    CHECK (role IN ('owner', 'viewer'));
+                                ALTER TABLE users ADD COLUMN owner_id INTEGER REFERENCES users(id);
+                                CREATE INDEX IF NOT EXISTS idx_users_owner_id ON users(owner_id);
+                            ",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v13 — money as INTEGER cents (money.rs)
+        //
+        // REAL dollars drifted by fractions of a cent when
+        // summed. Each column is copied into an INTEGER one,
+        // rounded to the nearest cent, and swapped in place.
+        // Encrypted values (TEXT, 'enc1:…') are copied as-is;
+        // vault.rs still reads their dollars.
+        // -------------------------------------------------------
+        Migration {
+            version: 13,
+            description: "money_as_integer_cents",
+            sql: "
+                                ALTER TABLE sessions ADD COLUMN total_earnings_cents INTEGER;
+                                ALTER TABLE sessions ADD COLUMN base_pay_cents       INTEGER;
+                                ALTER TABLE sessions ADD COLUMN tips_cents           INTEGER;
+                                UPDATE sessions SET
+                                    total_earnings_cents = CASE typeof(total_earnings)
+                                        WHEN 'real'    THEN CAST(ROUND(total_earnings * 100) AS INTEGER)
+                                        WHEN 'integer' THEN total_earnings * 100
+                                        ELSE total_earnings END,
+                                    base_pay_cents = CASE typeof(base_pay)
+                                        WHEN 'real'    THEN CAST(ROUND(base_pay * 100) AS INTEGER)
+                                        WHEN 'integer' THEN base_pay * 100
+                                        ELSE base_pay END,
+                                    tips_cents = CASE typeof(tips)
+                                        WHEN 'real'    THEN CAST(ROUND(tips * 100) AS INTEGER)
+                                        WHEN 'integer' THEN tips * 100
+                                        ELSE tips END;
+                                ALTER TABLE sessions DROP COLUMN total_earnings;
+                                ALTER TABLE sessions DROP COLUMN base_pay;
+                                ALTER TABLE sessions DROP COLUMN tips;
+                                ALTER TABLE sessions RENAME COLUMN total_earnings_cents TO total_earnings;
+                                ALTER TABLE sessions RENAME COLUMN base_pay_cents       TO base_pay;
+                                ALTER TABLE sessions RENAME COLUMN tips_cents           TO tips;
+
+                                ALTER TABLE offers ADD COLUMN total_earnings_cents INTEGER;
+                                UPDATE offers SET
+                                    total_earnings_cents = CASE typeof(total_earnings)
+                                        WHEN 'real'    THEN CAST(ROUND(total_earnings * 100) AS INTEGER)
+                                        WHEN 'integer' THEN total_earnings * 100
+                                        ELSE total_earnings END;
+                                ALTER TABLE offers DROP COLUMN total_earnings;
+                                ALTER TABLE offers RENAME COLUMN total_earnings_cents TO total_earnings;
+                            ",
+            kind: MigrationKind::Up,
+        },
+        // -------------------------------------------------------
+        // v14 — renamed accounts keep their old spelling for login
+        //
+        // Until resolved_at is set by the renamed account's
+        // first successful login, its exact pre-v10 username
+        // still reaches it (see username::login_candidates).
+        // -------------------------------------------------------
+        Migration {
+            version: 14,
+            description: "add_username_conflicts_resolved_at",
+            sql: "
+                                ALTER TABLE username_conflicts ADD COLUMN resolved_at INTEGER;
+                            ",
+            kind: MigrationKind::Up,
+        },
+    ]
+}
 
-                    ],
-                )
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(
+            Builder::default()
+                .add_migrations(db::DB_URL, migrations())
                 .build(),
         )
         .plugin(tauri_plugin_opener::init())
         .manage(AuthState::new())
-        .invoke_handler(tauri::generate_handler![
-            hash_password,
-            verify_password,
-            set_session,
+        .setup(|app| {
+            // The SQL plugin has run the migrations by now
+            tauri::async_runtime::block_on(async {
+                let pool = db::pool(&app.state::<tauri_plugin_sql::DbInstances>()).await?;
+                username::canonicalize_existing(&pool).await?;
+                app.state::<AuthState>().load_idle_timeout(&pool).await
+            })?;
+            spawn_lock_watcher(app.handle().clone());
+            Ok(())
+        })
+        .invoke_handler(guarded(tauri::generate_handler![
+            set_pin,
+            remove_pin,
+            pin_status,
+            unlock_with_pin,
+            register,
+            login,
+            switch_user,
+            restore_session,
+            change_password,
+            delete_account,
+            get_argon2_policy,
+            calibrate_argon2,
             clear_session,
             get_current_user,
-            check_auth_status
-        ])
+            check_auth_status,
+            touch_session,
+            set_idle_timeout,
+            create_session,
+            list_sessions,
+            query_sessions,
+            query_all_sessions,
+            get_session,
+            get_session_with_offers,
+            update_session,
+            delete_session,
+            save_session_with_offers,
+            create_offer,
+            create_offers,
+            list_offers,
+            update_offer,
+            delete_offer,
+            delete_session_offers,
+            begin_totp_enrollment,
+            confirm_totp_enrollment,
+            disable_totp,
+            totp_status,
+            recover_account,
+            regenerate_recovery_codes,
+            recovery_codes_remaining,
+            list_audit_log,
+            verify_audit_log,
+            record_export,
+            enable_encryption,
+            disable_encryption,
+            encryption_status,
+            list_username_conflicts,
+            list_recent_profiles,
+            forget_recent_profile,
+            check_password_strength,
+            create_viewer,
+            list_viewers,
+            remove_viewer
+        ]))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+    use tauri::test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime, INVOKE_KEY};
+    use tauri::webview::InvokeRequest;
+    use tauri::{App, Manager, WebviewWindow, WebviewWindowBuilder};
+
+    // An app with the real guard around a few commands, and no session
+    fn app() -> (App<MockRuntime>, WebviewWindow<MockRuntime>) {
+        let app = mock_builder()
+            .manage(AuthState::new())
+            .invoke_handler(guarded(tauri::generate_handler![
+                list_sessions,
+                touch_session,
+                get_current_user,
+                check_auth_status
+            ]))
+            .build(mock_context(noop_assets()))
+            .expect("build mock app");
+        let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .expect("build mock webview");
+        (app, webview)
+    }
+
+    fn invoke(webview: &WebviewWindow<MockRuntime>, cmd: &str) -> Result<serde_json::Value, serde_json::Value> {
+        get_ipc_response(
+            webview,
+            InvokeRequest {
+                cmd: cmd.into(),
+                callback: tauri::ipc::CallbackFn(0),
+                error: tauri::ipc::CallbackFn(1),
+                url: "http://tauri.localhost".parse().unwrap(),
+                body: tauri::ipc::InvokeBody::default(),
+                headers: Default::default(),
+                invoke_key: INVOKE_KEY.to_string(),
+            },
+        )
+        .map(|body| body.deserialize().expect("JSON response"))
+    }
+
+    fn error_code(response: Result<serde_json::Value, serde_json::Value>) -> String {
+        let error = response.expect_err("command should be rejected");
+        error["code"].as_str().expect("error code").to_string()
+    }
+
+    #[test]
+    fn protected_commands_need_a_session() {
+        let (_app, webview) = app();
+        assert_eq!(error_code(invoke(&webview, "list_sessions")), "unauthenticated");
+        assert_eq!(error_code(invoke(&webview, "touch_session")), "unauthenticated");
+    }
+
+    #[test]
+    fn public_commands_run_without_a_session() {
+        let (_app, webview) = app();
+        assert_eq!(invoke(&webview, "check_auth_status"), Ok(serde_json::json!(false)));
+        assert_eq!(invoke(&webview, "get_current_user"), Ok(serde_json::Value::Null));
+    }
+
+    #[test]
+    fn lapsed_sessions_are_rejected() {
+        let (app, webview) = app();
+        let state = app.state::<AuthState>();
+        state.start(1, "driver".into(), (roles::Role::Owner, 1), None).unwrap();
+        assert_eq!(invoke(&webview, "touch_session"), Ok(serde_json::Value::Null));
+        assert_eq!(invoke(&webview, "check_auth_status"), Ok(serde_json::json!(true)));
+
+        // Any idle time now exceeds the timeout
+        state.idle_timeout_secs.store(0, Ordering::Relaxed);
+        assert_eq!(error_code(invoke(&webview, "touch_session")), "unauthenticated");
+        assert_eq!(error_code(invoke(&webview, "list_sessions")), "unauthenticated");
+        assert_eq!(invoke(&webview, "check_auth_status"), Ok(serde_json::json!(false)));
+    }
+}
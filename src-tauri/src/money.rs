@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// ---------------------------------------------------------------------------
+// Money as a whole number of cents (migration v13).
+//
+// Amounts cross the command boundary as integer cents and are stored as
+// INTEGER cents, so sums are exact. Sums are checked: an overflow is a bug
+// or hostile input, never something to wrap around silently. Dollars
+// only appear at the edges — formatted for messages, and in encrypted
+// values written before v13.
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const fn from_cents(cents: i64) -> Money {
+        Money(cents)
+    }
+
+    pub const fn cents(self) -> i64 {
+        self.0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        self.0.checked_add(other.0).map(Money)
+    }
+
+    // Nearest cent to a dollar amount (values stored before v13); None for
+    // NaN, infinities and amounts beyond i64 cents
+    pub fn from_dollars(dollars: f64) -> Option<Money> {
+        let cents = (dollars * 100.0).round();
+        (cents.is_finite() && cents.abs() < i64::MAX as f64).then_some(Money(cents as i64))
+    }
+}
+
+// "$1,234.56" / "-$0.50"
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cents = self.0.unsigned_abs();
+        let dollars = (cents / 100).to_string();
+        let mut grouped = String::with_capacity(dollars.len() + dollars.len() / 3);
+        for (i, digit) in dollars.chars().enumerate() {
+            if i > 0 && (dollars.len() - i).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        let sign = if self.is_negative() { "-" } else { "" };
+        write!(f, "{}${}.{:02}", sign, grouped, cents % 100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_dollars_with_grouping() {
+        let cases = [
+            (0, "$0.00"),
+            (5, "$0.05"),
+            (99, "$0.99"),
+            (100, "$1.00"),
+            (123_456, "$1,234.56"),
+            (100_000_000, "$1,000,000.00"),
+            (-50, "-$0.50"),
+            (-123_456_789, "-$1,234,567.89"),
+            (i64::MIN, "-$92,233,720,368,547,758.08"),
+        ];
+        for (cents, expected) in cases {
+            assert_eq!(Money::from_cents(cents).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn rounds_dollars_to_the_nearest_cent() {
+        assert_eq!(Money::from_dollars(12.34), Some(Money::from_cents(1_234)));
+        // 0.1 + 0.2 is 0.30000000000000004 as f64
+        assert_eq!(Money::from_dollars(0.1 + 0.2), Some(Money::from_cents(30)));
+        assert_eq!(Money::from_dollars(19.999), Some(Money::from_cents(2_000)));
+        assert_eq!(Money::from_dollars(0.005), Some(Money::from_cents(1)));
+        assert_eq!(Money::from_dollars(-2.5), Some(Money::from_cents(-250)));
+    }
+
+    #[test]
+    fn rejects_dollars_that_are_not_amounts() {
+        assert_eq!(Money::from_dollars(f64::NAN), None);
+        assert_eq!(Money::from_dollars(f64::INFINITY), None);
+        assert_eq!(Money::from_dollars(f64::NEG_INFINITY), None);
+        assert_eq!(Money::from_dollars(1e17), None);
+        assert_eq!(Money::from_dollars(-1e17), None);
+        assert!(Money::from_dollars(1e15).is_some());
+    }
+
+    #[test]
+    fn sums_refuse_to_overflow() {
+        let one = Money::from_cents(1);
+        assert_eq!(Money::from_cents(150).checked_add(one), Some(Money::from_cents(151)));
+        assert_eq!(Money::from_cents(i64::MAX).checked_add(one), None);
+        assert_eq!(Money::from_cents(i64::MIN).checked_add(Money::from_cents(-1)), None);
+    }
+
+    #[test]
+    fn serializes_as_bare_cents() {
+        assert_eq!(serde_json::to_string(&Money::from_cents(1_234)).unwrap(), "1234");
+        assert_eq!(serde_json::from_str::<Money>("-5").unwrap(), Money::from_cents(-5));
+        assert!(serde_json::from_str::<Money>("12.5").is_err());
+    }
+}
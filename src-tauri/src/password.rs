@@ -0,0 +1,261 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::{DashlensError, Result};
+
+// ---------------------------------------------------------------------------
+// Argon2 hashing policy.
+//
+// New hashes are always produced with the current policy. Verification reads
+// the algorithm and cost parameters from the stored PHC string, so hashes
+// made under an older policy keep working; `needs_rehash` tells the login
+// path when one should be upgraded in place.
+//
+// The policy is stored as JSON in app_settings under POLICY_SETTING and is
+// normally chosen by `calibrate`, which benchmarks the device so a
+// verification takes roughly a target time.
+// ---------------------------------------------------------------------------
+
+const POLICY_SETTING: &str = "argon2_policy";
+
+// Target verification time used when the caller does not specify one
+pub const DEFAULT_CALIBRATION_TARGET: Duration = Duration::from_millis(250);
+
+// Security floor: calibration never goes below these, even on slow devices
+const MIN_M_COST: u32 = 8 * 1024; // 8 MiB
+const MIN_T_COST: u32 = 2;
+// Upper bound on memory so low-RAM phones are not pushed into swapping
+const MAX_M_COST: u32 = 256 * 1024; // 256 MiB
+const MAX_T_COST: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Argon2Variant {
+    Argon2d,
+    Argon2i,
+    Argon2id,
+}
+
+impl Argon2Variant {
+    fn algorithm(self) -> Algorithm {
+        match self {
+            Argon2Variant::Argon2d => Algorithm::Argon2d,
+            Argon2Variant::Argon2i => Algorithm::Argon2i,
+            Argon2Variant::Argon2id => Algorithm::Argon2id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Argon2Policy {
+    pub variant: Argon2Variant,
+    pub m_cost: u32, // memory in KiB
+    pub t_cost: u32, // iterations
+    pub p_cost: u32, // lanes
+}
+
+impl Default for Argon2Policy {
+    fn default() -> Self {
+        Self {
+            variant: Argon2Variant::Argon2id,
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Argon2Policy {
+    pub fn hasher(&self) -> Result<Argon2<'static>> {
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, None)
+            .map_err(|e| DashlensError::Validation(format!("Invalid Argon2 parameters: {}", e)))?;
+        Ok(Argon2::new(self.variant.algorithm(), Version::V0x13, params))
+    }
+
+    // Whether a stored hash was produced with settings other than this policy
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hash) else {
+            return true;
+        };
+        let Ok(params) = Params::try_from(&parsed) else {
+            return true;
+        };
+        parsed.algorithm != self.variant.algorithm().ident()
+            || parsed.version != Some(u32::from(Version::V0x13))
+            || params.m_cost() != self.m_cost
+            || params.t_cost() != self.t_cost
+            || params.p_cost() != self.p_cost
+    }
+}
+
+// Policy new hashes should use; falls back to the default when none is stored
+pub async fn current_policy(pool: &Pool<Sqlite>) -> Result<Argon2Policy> {
+    let stored = crate::settings::get(pool, POLICY_SETTING).await?;
+    Ok(stored
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+pub async fn save_policy(pool: &Pool<Sqlite>, policy: &Argon2Policy) -> Result<()> {
+    policy.hasher()?; // reject parameter sets argon2 would refuse
+    let json = serde_json::to_string(policy)
+        .map_err(|e| DashlensError::Internal(format!("Failed to encode Argon2 policy: {}", e)))?;
+    crate::settings::set(pool, POLICY_SETTING, &json).await
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalibrationResult {
+    pub policy: Argon2Policy,
+    pub measured_ms: u64,
+}
+
+// Time a single hash under the policy
+fn measure(policy: &Argon2Policy) -> Result<Duration> {
+    let start = Instant::now();
+    hash("calibration-probe", policy)?;
+    Ok(start.elapsed())
+}
+
+// Benchmark this device and pick Argon2id parameters that make one
+// verification take about `target`. Memory is scaled first (it is the
+// stronger defence against GPU attacks); iterations are only raised once
+// memory hits MAX_M_COST. Blocking — run it off the async runtime.
+pub fn calibrate(target: Duration) -> Result<CalibrationResult> {
+    let mut policy = Argon2Policy {
+        variant: Argon2Variant::Argon2id,
+        m_cost: Params::DEFAULT_M_COST,
+        t_cost: MIN_T_COST,
+        p_cost: 1,
+    };
+
+    let elapsed = measure(&policy)?;
+    let scale = target.as_secs_f64() / elapsed.as_secs_f64().max(1e-3);
+    policy.m_cost = ((policy.m_cost as f64 * scale) as u32).clamp(MIN_M_COST, MAX_M_COST);
+
+    if policy.m_cost == MAX_M_COST {
+        let elapsed = measure(&policy)?;
+        let scale = target.as_secs_f64() / elapsed.as_secs_f64().max(1e-3);
+        policy.t_cost = ((policy.t_cost as f64 * scale).round() as u32).clamp(MIN_T_COST, MAX_T_COST);
+    }
+
+    let measured = measure(&policy)?;
+    Ok(CalibrationResult {
+        policy,
+        measured_ms: measured.as_millis() as u64,
+    })
+}
+
+// Hash a password with a fresh random salt under the given policy
+pub fn hash(password: &str, policy: &Argon2Policy) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    policy
+        .hasher()?
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| DashlensError::Internal(format!("Failed to hash password: {}", e)))
+}
+
+// Check a password against a stored PHC-format Argon2 hash
+pub fn verify(password: &str, hash: &str) -> Result<bool> {
+    let parsed_hash = PasswordHash::new(hash)
+        .map_err(|e| DashlensError::Internal(format!("Failed to parse hash: {}", e)))?;
+
+    // Algorithm and params come from the hash itself, not from this instance
+    let argon2 = Argon2::default();
+
+    Ok(argon2.verify_password(password.as_bytes(), &parsed_hash).is_ok())
+}
+
+// Spend the same Argon2 work as `verify` when there is no stored hash to
+// check (an unknown username), so response time does not reveal which
+// accounts exist. The throwaway hash is made once per policy and cached.
+pub fn verify_dummy(password: &str, policy: &Argon2Policy) -> Result<()> {
+    static DUMMY_HASH: Mutex<Option<(Argon2Policy, String)>> = Mutex::new(None);
+    let dummy_hash = {
+        let mut cached = DUMMY_HASH.lock()?;
+        match cached.as_ref() {
+            Some((cached_policy, dummy_hash)) if cached_policy == policy => dummy_hash.clone(),
+            _ => {
+                let dummy_hash = hash("dashlens-dummy-password", policy)?;
+                *cached = Some((*policy, dummy_hash.clone()));
+                dummy_hash
+            }
+        }
+    };
+    verify(password, &dummy_hash)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(m_cost: u32, t_cost: u32) -> Argon2Policy {
+        Argon2Policy {
+            variant: Argon2Variant::Argon2id,
+            m_cost,
+            t_cost,
+            p_cost: 1,
+        }
+    }
+
+    #[test]
+    fn hashes_verify_under_their_own_params() {
+        let weak = hash("hunter2", &policy(64, 1)).unwrap();
+        assert!(verify("hunter2", &weak).unwrap());
+        assert!(!verify("hunter3", &weak).unwrap());
+        assert!(verify("anything", "not a phc string").is_err());
+    }
+
+    #[test]
+    fn hashes_under_weaker_params_need_rehash() {
+        let current = policy(128, 2);
+        let fresh = hash("hunter2", &current).unwrap();
+        assert!(!current.needs_rehash(&fresh));
+
+        for older in [policy(64, 2), policy(128, 1), Argon2Policy { p_cost: 2, ..current }] {
+            assert!(current.needs_rehash(&hash("hunter2", &older).unwrap()), "{:?}", older);
+        }
+        let argon2i = Argon2Policy {
+            variant: Argon2Variant::Argon2i,
+            ..current
+        };
+        assert!(current.needs_rehash(&hash("hunter2", &argon2i).unwrap()));
+        assert!(current.needs_rehash("$2b$12$not-an-argon2-hash"));
+    }
+
+    #[test]
+    fn policy_round_trips_through_settings() {
+        tauri::async_runtime::block_on(async {
+            let pool = crate::db::memory_pool().await;
+            assert_eq!(current_policy(&pool).await.unwrap(), Argon2Policy::default());
+
+            let saved = policy(32 * 1024, 3);
+            save_policy(&pool, &saved).await.unwrap();
+            assert_eq!(current_policy(&pool).await.unwrap(), saved);
+
+            // Parameters argon2 refuses are never stored
+            assert!(save_policy(&pool, &policy(1, 1)).await.is_err());
+            assert_eq!(current_policy(&pool).await.unwrap(), saved);
+
+            // An unreadable stored value falls back to the default
+            crate::settings::set(&pool, POLICY_SETTING, "{}").await.unwrap();
+            assert_eq!(current_policy(&pool).await.unwrap(), Argon2Policy::default());
+        });
+    }
+
+    // A target no device can meet lands on the security floor
+    #[test]
+    fn calibration_respects_the_floor() {
+        let result = calibrate(Duration::from_micros(1)).unwrap();
+        assert_eq!(result.policy, policy(MIN_M_COST, MIN_T_COST));
+        assert!(result.policy.hasher().is_ok());
+    }
+}
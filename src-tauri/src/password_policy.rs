@@ -0,0 +1,376 @@
+use flate2::read::GzDecoder;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+use crate::error::{DashlensError, Result};
+use crate::secret::SecretString;
+
+// ---------------------------------------------------------------------------
+// Rules for new passwords (register, change_password, recover_account and
+// new viewer accounts).
+//
+// Strength is estimated the way zxcvbn does it, in miniature: the password
+// is split greedily into the cheapest patterns an attacker would try first
+// (common passwords, the username, repeats, sequences and keyboard runs,
+// years), everything else is brute force over the character classes used,
+// and the summed log10 guess count maps to a 0–4 score. Passwords on the
+// bundled breached-password list are rejected outright.
+//
+// resources/breached-passwords.txt.gz is one lowercase password per line,
+// most common first, gzip-compressed (`gzip -9n`). Position in the file is
+// the rank used for guess estimates. scripts/update-breached-passwords.sh
+// rebuilds it from a public top-10,000 list; the copy checked in so far
+// holds only the most common few hundred entries.
+// ---------------------------------------------------------------------------
+
+pub const MIN_LENGTH: usize = 8;
+pub const MAX_LENGTH: usize = 256;
+pub const MIN_SCORE: u8 = 3;
+
+const BREACHED_LIST: &[u8] = include_bytes!("../resources/breached-passwords.txt.gz");
+
+// Shortest list entry / user input matched inside a longer password
+const MIN_WORD_LEN: usize = 4;
+const MIN_USER_INPUT_LEN: usize = 3;
+
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+// log10 guess counts at which the score steps up (zxcvbn's thresholds)
+const SCORE_THRESHOLDS: [f64; 4] = [3.0, 6.0, 8.0, 10.0];
+
+// What the register / change-password forms show under the password field
+#[derive(Debug, Clone, Serialize)]
+pub struct PasswordFeedback {
+    pub score: u8, // 0 (trivial) – 4 (very strong)
+    pub guesses_log10: f64,
+    pub acceptable: bool,
+    pub breached: bool,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Pattern {
+    // Ordered by how much the warning matters, most first
+    UserInput,
+    Dictionary,
+    Sequence,
+    Repeat,
+    Year,
+}
+
+impl Pattern {
+    fn warning(self) -> &'static str {
+        match self {
+            Pattern::UserInput => "Passwords that contain your username are easy to guess",
+            Pattern::Dictionary => "This is similar to a commonly used password",
+            Pattern::Sequence => "Sequences like abc, 6543 or qwerty are easy to guess",
+            Pattern::Repeat => "Repeated characters like aaa are easy to guess",
+            Pattern::Year => "Recent years are easy to guess",
+        }
+    }
+
+    fn suggestion(self) -> &'static str {
+        match self {
+            Pattern::UserInput => "Leave your username out of the password",
+            Pattern::Dictionary => "Avoid common passwords and words on their own",
+            Pattern::Sequence => "Avoid sequences and keyboard patterns",
+            Pattern::Repeat => "Avoid repeated words and characters",
+            Pattern::Year => "Avoid years that are associated with you",
+        }
+    }
+}
+
+// Lowercase breached password -> rank (1 = most common)
+fn breached_list() -> &'static HashMap<String, usize> {
+    static LIST: OnceLock<HashMap<String, usize>> = OnceLock::new();
+    LIST.get_or_init(|| {
+        let mut text = String::new();
+        if GzDecoder::new(BREACHED_LIST).read_to_string(&mut text).is_err() {
+            return HashMap::new();
+        }
+        let mut list = HashMap::new();
+        for (index, line) in text.lines().map(str::trim).filter(|line| !line.is_empty()).enumerate() {
+            list.entry(line.to_string()).or_insert(index + 1);
+        }
+        list
+    })
+}
+
+// Undo the usual character substitutions (p@ssw0rd -> password)
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        _ => c,
+    }
+}
+
+// Size of the alphabet the password draws from
+fn cardinality(chars: &[char]) -> f64 {
+    let mut size = 0.0;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        size += 26.0;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        size += 26.0;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        size += 10.0;
+    }
+    if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') {
+        size += 33.0;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        size += 100.0;
+    }
+    f64::max(size, 10.0)
+}
+
+fn keyboard_adjacent(a: char, b: char) -> bool {
+    KEYBOARD_ROWS.iter().any(|row| {
+        let row: Vec<char> = row.chars().collect();
+        row.windows(2).any(|pair| (pair[0] == a && pair[1] == b) || (pair[0] == b && pair[1] == a))
+    })
+}
+
+fn sequential(a: char, b: char) -> bool {
+    (a as i64 - b as i64).abs() == 1 || keyboard_adjacent(a, b)
+}
+
+// Longest entry of `words` starting at `start`, with its rank
+fn longest_word(text: &[char], start: usize, min_len: usize, words: &HashMap<String, usize>) -> Option<(usize, usize)> {
+    let max_len = (text.len() - start).min(32);
+    (min_len..=max_len).rev().find_map(|len| {
+        let candidate: Zeroizing<String> = Zeroizing::new(text[start..start + len].iter().collect());
+        words.get(candidate.as_str()).map(|rank| (len, *rank))
+    })
+}
+
+fn is_year(chars: &[char]) -> bool {
+    let digits: Zeroizing<String> = Zeroizing::new(chars.iter().collect());
+    chars.iter().all(char::is_ascii_digit) && matches!(digits.parse::<u32>(), Ok(year) if (1900..=2099).contains(&year))
+}
+
+// Strength of `password` for an account called `username`
+pub fn evaluate(password: &str, username: &str) -> PasswordFeedback {
+    // Every copy of the password made here is wiped on return
+    let chars: Zeroizing<Vec<char>> = Zeroizing::new(password.chars().collect());
+    let lower: Zeroizing<Vec<char>> = Zeroizing::new(Zeroizing::new(password.to_lowercase()).chars().collect());
+    let plain: Zeroizing<Vec<char>> = Zeroizing::new(lower.iter().copied().map(unleet).collect());
+    let list = breached_list();
+
+    let lower_text: Zeroizing<String> = Zeroizing::new(lower.iter().collect());
+    let plain_text: Zeroizing<String> = Zeroizing::new(plain.iter().collect());
+    let breached = list.contains_key(lower_text.as_str()) || list.contains_key(plain_text.as_str());
+
+    let mut user_inputs = HashMap::new();
+    let username = crate::username::normalize(username);
+    if username.chars().count() >= MIN_USER_INPUT_LEN {
+        user_inputs.insert(username, 1);
+    }
+
+    // Case folding can change the length (e.g. 'İ'); fall back to plain brute force
+    let aligned = lower.len() == chars.len();
+    let per_char = cardinality(&chars).log10();
+
+    let mut guesses_log10 = 0.0;
+    let mut found: Vec<Pattern> = Vec::new();
+    let mut substituted = false;
+    let mut i = 0;
+    while i < chars.len() {
+        if !aligned {
+            guesses_log10 += per_char;
+            i += 1;
+            continue;
+        }
+
+        let user_match = longest_word(&lower, i, MIN_USER_INPUT_LEN, &user_inputs)
+            .or_else(|| longest_word(&plain, i, MIN_USER_INPUT_LEN, &user_inputs));
+        if let Some((len, _)) = user_match {
+            guesses_log10 += 1.0;
+            found.push(Pattern::UserInput);
+            i += len;
+            continue;
+        }
+
+        let word_match = longest_word(&lower, i, MIN_WORD_LEN, list)
+            .map(|word| (word, false))
+            .or_else(|| longest_word(&plain, i, MIN_WORD_LEN, list).map(|word| (word, true)));
+        if let Some(((len, rank), leet)) = word_match {
+            guesses_log10 += (rank as f64).log10();
+            if chars[i..i + len].iter().any(|c| c.is_uppercase()) {
+                guesses_log10 += 2f64.log10();
+            }
+            if leet {
+                guesses_log10 += 2f64.log10();
+                substituted = true;
+            }
+            found.push(Pattern::Dictionary);
+            i += len;
+            continue;
+        }
+
+        if i + 4 <= chars.len() && is_year(&chars[i..i + 4]) {
+            guesses_log10 += 120f64.log10();
+            found.push(Pattern::Year);
+            i += 4;
+            continue;
+        }
+
+        let repeat_len = lower[i..].iter().take_while(|c| **c == lower[i]).count();
+        if repeat_len >= 3 {
+            guesses_log10 += per_char + (repeat_len as f64).log10();
+            found.push(Pattern::Repeat);
+            i += repeat_len;
+            continue;
+        }
+
+        let sequence_len = 1 + lower[i..].windows(2).take_while(|pair| sequential(pair[0], pair[1])).count();
+        if sequence_len >= 3 {
+            guesses_log10 += per_char + (sequence_len as f64).log10() + 2f64.log10();
+            found.push(Pattern::Sequence);
+            i += sequence_len;
+            continue;
+        }
+
+        guesses_log10 += per_char;
+        i += 1;
+    }
+
+    let score = if breached {
+        0
+    } else {
+        SCORE_THRESHOLDS.iter().filter(|threshold| guesses_log10 >= **threshold).count() as u8
+    };
+    let length = chars.len();
+    let acceptable = !breached && score >= MIN_SCORE && (MIN_LENGTH..=MAX_LENGTH).contains(&length);
+
+    found.sort();
+    found.dedup();
+    let warning = if breached {
+        Some("This password has appeared in data breaches".to_string())
+    } else if length < MIN_LENGTH {
+        Some(format!("Password must be at least {} characters", MIN_LENGTH))
+    } else if length > MAX_LENGTH {
+        Some(format!("Password must be at most {} characters", MAX_LENGTH))
+    } else if score < MIN_SCORE {
+        Some(found.first().map_or("This password is easy to guess", |pattern| pattern.warning()).to_string())
+    } else {
+        None
+    };
+
+    let mut suggestions = Vec::new();
+    if score < MIN_SCORE || breached {
+        suggestions.push("Add another word or two. Uncommon words are better.".to_string());
+        suggestions.extend(found.iter().map(|pattern| pattern.suggestion().to_string()));
+        if substituted {
+            suggestions.push("Predictable substitutions like '@' instead of 'a' don't help much".to_string());
+        }
+    }
+
+    PasswordFeedback {
+        score,
+        guesses_log10: (guesses_log10 * 100.0).round() / 100.0,
+        acceptable,
+        breached,
+        warning,
+        suggestions,
+    }
+}
+
+// Reject a new password that does not meet the policy, with the feedback
+// attached for the form
+pub fn enforce(password: &str, username: &str) -> Result<()> {
+    let feedback = evaluate(password, username);
+    if feedback.acceptable {
+        Ok(())
+    } else {
+        Err(DashlensError::WeakPassword(Box::new(feedback)))
+    }
+}
+
+// Live strength meter for the register / change-password forms. Public:
+// registration happens before anyone is logged in.
+#[tauri::command]
+pub async fn check_password_strength(
+    password: SecretString,
+    username: Option<String>,
+) -> Result<PasswordFeedback> {
+    Ok(evaluate(&password, username.as_deref().unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_list_loads() {
+        let list = breached_list();
+        assert_eq!(list.get("123456"), Some(&1));
+        assert_eq!(list.get("password"), Some(&2));
+    }
+
+    #[test]
+    fn rejects_breached_passwords() {
+        let feedback = evaluate("password", "driver");
+        assert!(feedback.breached);
+        assert_eq!(feedback.score, 0);
+        assert!(!feedback.acceptable);
+        assert_eq!(feedback.warning.as_deref(), Some("This password has appeared in data breaches"));
+        assert!(enforce("password", "driver").is_err());
+    }
+
+    #[test]
+    fn sees_through_leet_and_case() {
+        for variant in ["P@ssw0rd", "PASSWORD", "p4$$w0rd"] {
+            let feedback = evaluate(variant, "driver");
+            assert!(feedback.breached, "{} should count as breached", variant);
+            assert!(!feedback.acceptable);
+        }
+    }
+
+    #[test]
+    fn penalizes_the_username() {
+        let password = "samrivera2019";
+        let with_name = evaluate(password, "SamRivera");
+        let without = evaluate(password, "driver");
+        assert!(with_name.guesses_log10 < without.guesses_log10);
+        assert!(!with_name.acceptable);
+        assert_eq!(with_name.warning.as_deref(), Some(Pattern::UserInput.warning()));
+    }
+
+    #[test]
+    fn penalizes_keyboard_runs() {
+        // Right to left along the rows, so no list entry matches first
+        let feedback = evaluate("poiuytrewqlkjhgfdsa", "driver");
+        assert!(!feedback.acceptable);
+        assert_eq!(feedback.warning.as_deref(), Some(Pattern::Sequence.warning()));
+    }
+
+    #[test]
+    fn accepts_a_long_passphrase() {
+        let feedback = evaluate("cobalt lantern drifts over quiet harbors", "driver");
+        assert!(feedback.acceptable, "{:?}", feedback);
+        assert_eq!(feedback.score, 4);
+        assert_eq!(feedback.warning, None);
+        assert!(feedback.suggestions.is_empty());
+        assert!(enforce("cobalt lantern drifts over quiet harbors", "driver").is_ok());
+    }
+
+    #[test]
+    fn enforces_length_limits() {
+        assert_eq!(
+            evaluate("x7#Qz", "driver").warning,
+            Some(format!("Password must be at least {} characters", MIN_LENGTH))
+        );
+        assert!(!evaluate(&"x7#Qz".repeat(60), "driver").acceptable);
+    }
+}
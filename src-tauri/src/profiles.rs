@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use tauri::State;
+use tauri_plugin_sql::DbInstances;
+
+use crate::auth::now_secs;
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+
+// ---------------------------------------------------------------------------
+// Profiles recently used on this device, most recent first, for the
+// profile picker on the login screen. Stored as JSON in app_settings; only
+// ids and usernames are kept.
+// ---------------------------------------------------------------------------
+
+const RECENT_PROFILES_SETTING: &str = "recent_profiles";
+const MAX_RECENT_PROFILES: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentProfile {
+    pub user_id: i64,
+    pub username: String,
+    pub last_used_at: i64, // unix seconds
+}
+
+async fn load(conn: &mut SqliteConnection) -> Result<Vec<RecentProfile>> {
+    let stored = crate::settings::get(&mut *conn, RECENT_PROFILES_SETTING).await?;
+    Ok(stored
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+async fn save(conn: &mut SqliteConnection, profiles: &[RecentProfile]) -> Result<()> {
+    let json = serde_json::to_string(profiles)
+        .map_err(|e| DashlensError::Internal(format!("Failed to encode recent profiles: {}", e)))?;
+    crate::settings::set(&mut *conn, RECENT_PROFILES_SETTING, &json).await
+}
+
+// Move the user to the front of the list. Like `forget`, a read-modify-write
+// of one setting, so it holds the write lock throughout.
+pub async fn touch(pool: &Pool<Sqlite>, user_id: i64, username: &str) -> Result<()> {
+    let mut tx = crate::db::begin_write(pool).await?;
+    let mut profiles = load(&mut tx).await?;
+    profiles.retain(|profile| profile.user_id != user_id);
+    profiles.insert(
+        0,
+        RecentProfile {
+            user_id,
+            username: username.to_string(),
+            last_used_at: now_secs(),
+        },
+    );
+    profiles.truncate(MAX_RECENT_PROFILES);
+    save(&mut tx, &profiles).await?;
+    tx.commit().await.map_err(DashlensError::database("write setting"))?;
+    Ok(())
+}
+
+pub async fn forget(pool: &Pool<Sqlite>, user_id: i64) -> Result<()> {
+    let mut tx = crate::db::begin_write(pool).await?;
+    let mut profiles = load(&mut tx).await?;
+    profiles.retain(|profile| profile.user_id != user_id);
+    save(&mut tx, &profiles).await?;
+    tx.commit().await.map_err(DashlensError::database("write setting"))?;
+    Ok(())
+}
+
+// Public: the login screen shows these before anyone is logged in. Accounts
+// deleted or renamed since are dropped or shown under their current name.
+#[tauri::command]
+pub async fn list_recent_profiles(
+    db: State<'_, DbInstances>,
+) -> Result<Vec<RecentProfile>> {
+    let pool = crate::db::pool(&db).await?;
+    let mut conn = pool.acquire().await.map_err(DashlensError::database("open connection"))?;
+    let mut current = Vec::new();
+    for profile in load(&mut conn).await? {
+        let user: Option<(String,)> = sqlx::query_as("SELECT username FROM users WHERE id = $1")
+            .bind(profile.user_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(DashlensError::database("look up user"))?;
+        if let Some((username,)) = user {
+            current.push(RecentProfile { username, ..profile });
+        }
+    }
+    Ok(current)
+}
+
+// Remove the logged-in user from this device's profile list
+#[tauri::command]
+pub async fn forget_recent_profile(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<()> {
+    let pool = crate::db::pool(&db).await?;
+    forget(&pool, user.user_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn listed(pool: &Pool<Sqlite>) -> Vec<(i64, String)> {
+        let mut conn = pool.acquire().await.unwrap();
+        load(&mut conn).await.unwrap().into_iter().map(|p| (p.user_id, p.username)).collect()
+    }
+
+    #[test]
+    fn most_recent_first_without_duplicates() {
+        tauri::async_runtime::block_on(async {
+            let pool = crate::db::memory_pool().await;
+            for user_id in 1..=7 {
+                touch(&pool, user_id, &format!("user{}", user_id)).await.unwrap();
+            }
+            touch(&pool, 5, "user5").await.unwrap();
+            let ids: Vec<i64> = listed(&pool).await.into_iter().map(|(id, _)| id).collect();
+            assert_eq!(ids, [5, 7, 6, 4, 3]);
+
+            forget(&pool, 7).await.unwrap();
+            let ids: Vec<i64> = listed(&pool).await.into_iter().map(|(id, _)| id).collect();
+            assert_eq!(ids, [5, 6, 4, 3]);
+        });
+    }
+
+    // Concurrent logins each land in the list; none overwrites another's
+    // entry. Needs real concurrency, so a shared in-memory database with
+    // several connections rather than db::memory_pool.
+    #[test]
+    fn concurrent_touches_keep_every_entry() {
+        tauri::async_runtime::block_on(async {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(4)
+                .connect("sqlite:file:profiles_concurrent?mode=memory&cache=shared")
+                .await
+                .unwrap();
+            for migration in crate::migrations() {
+                sqlx::raw_sql(migration.sql).execute(&pool).await.unwrap();
+            }
+
+            let touches = (1..=4).map(|user_id| {
+                let pool = pool.clone();
+                tauri::async_runtime::spawn(async move { touch(&pool, user_id, "someone").await })
+            });
+            for handle in touches.collect::<Vec<_>>() {
+                handle.await.unwrap().unwrap();
+            }
+            let mut ids: Vec<i64> = listed(&pool).await.into_iter().map(|(id, _)| id).collect();
+            ids.sort();
+            assert_eq!(ids, [1, 2, 3, 4]);
+        });
+    }
+}
@@ -0,0 +1,385 @@
+use rand::{rngs::OsRng, Rng};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use tauri::State;
+use tauri_plugin_sql::DbInstances;
+use zeroize::Zeroizing;
+
+use crate::audit::{self, AuditEvent};
+use crate::auth::now_secs;
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+use crate::password::{self, Argon2Policy, Argon2Variant};
+use crate::secret::SecretString;
+use crate::throttle::Attempt;
+use crate::vault::{self, DataKey};
+
+// ---------------------------------------------------------------------------
+// Single-use recovery codes (table `recovery_codes`, migration v7).
+//
+// A fresh set is generated at registration and shown once; only Argon2
+// hashes are stored. `recover_account` consumes one code to set a new
+// password when the old one is forgotten. For encrypted accounts each code
+// also wraps the data key (see vault.rs) so recovery does not lose data.
+// ---------------------------------------------------------------------------
+
+const CODE_COUNT: usize = 10;
+const CODE_GROUP_LEN: usize = 5; // codes are two groups, e.g. "K7QX2-MP9RT"
+
+// No 0/O, 1/I/L — codes are read off paper and typed on a phone
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+// Codes carry ~49 bits of entropy, so a light fixed cost is enough and keeps
+// generating/checking ten of them fast on old phones.
+const CODE_POLICY: Argon2Policy = Argon2Policy {
+    variant: Argon2Variant::Argon2id,
+    m_cost: 8 * 1024,
+    t_cost: 2,
+    p_cost: 1,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct RecoverAccountRequest {
+    pub username: String,
+    pub code: SecretString,
+    pub new_password: SecretString,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoverAccountResponse {
+    pub remaining_codes: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegenerateCodesRequest {
+    pub password: SecretString,
+}
+
+fn generate_code() -> String {
+    let mut rng = OsRng;
+    let mut group = || -> String {
+        (0..CODE_GROUP_LEN)
+            .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+            .collect()
+    };
+    let first = group();
+    let second = group();
+    format!("{}-{}", first, second)
+}
+
+// Accept codes typed in any case, with or without the dash / spaces
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+// Replace the user's codes with a fresh set and return them in plaintext.
+// Takes a connection so callers can run it inside their own transaction.
+// Pass the user's data key when encryption is on.
+pub async fn replace_codes(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    data_key: Option<&DataKey>,
+) -> Result<Vec<String>> {
+    sqlx::query("DELETE FROM recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(DashlensError::database("clear recovery codes"))?;
+
+    let codes: Vec<String> = (0..CODE_COUNT).map(|_| generate_code()).collect();
+    for code in &codes {
+        let code_hash = password::hash(&normalize_code(code), &CODE_POLICY)?;
+        let wrapped_key = data_key
+            .map(|key| vault::wrap_key(key, &normalize_code(code), &CODE_POLICY))
+            .transpose()?;
+        sqlx::query(
+            "INSERT INTO recovery_codes (user_id, code_hash, wrapped_key, created_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(user_id)
+        .bind(&code_hash)
+        .bind(&wrapped_key)
+        .bind(now_secs())
+        .execute(&mut *conn)
+        .await
+        .map_err(DashlensError::database("store recovery code"))?;
+    }
+    Ok(codes)
+}
+
+// Set a new password using a recovery code. Attempts are throttled exactly
+// like logins, and a wrong username or code gives the same error.
+#[tauri::command]
+pub async fn recover_account(
+    db: State<'_, DbInstances>,
+    request: RecoverAccountRequest,
+) -> Result<RecoverAccountResponse> {
+    crate::password_policy::enforce(&request.new_password, &request.username)?;
+    let pool = crate::db::pool(&db).await?;
+    recover(&pool, &request).await
+}
+
+async fn recover(pool: &Pool<Sqlite>, request: &RecoverAccountRequest) -> Result<RecoverAccountResponse> {
+    let username = crate::username::normalize(&request.username);
+
+    let wait_if_failed = match crate::throttle::claim_attempt(pool, &username).await? {
+        Attempt::Claimed { wait_if_failed } => wait_if_failed,
+        Attempt::Locked { retry_after_secs } => return Err(DashlensError::Locked { retry_after_secs }),
+    };
+
+    let candidates = crate::username::login_candidates(pool, &request.username).await?;
+
+    let code = Zeroizing::new(normalize_code(&request.code));
+    let mut matched = None;
+    for (user_id, _, _) in &candidates {
+        let unused: Vec<(i64, String, Option<String>)> = sqlx::query_as(
+            "SELECT id, code_hash, wrapped_key FROM recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+        .map_err(DashlensError::database("read recovery codes"))?;
+        for (code_id, code_hash, wrapped_key) in unused {
+            if password::verify(&code, &code_hash)? {
+                matched = Some((*user_id, code_id, wrapped_key));
+                break;
+            }
+        }
+        if matched.is_some() {
+            break;
+        }
+    }
+    if candidates.is_empty() {
+        // As much Argon2 work as a wrong code for a real account
+        for _ in 0..CODE_COUNT {
+            password::verify_dummy(&code, &CODE_POLICY)?;
+        }
+    }
+
+    let Some((user_id, code_id, wrapped_key)) = matched else {
+        return Err(if wait_if_failed > 0 {
+            DashlensError::Locked {
+                retry_after_secs: wait_if_failed,
+            }
+        } else {
+            DashlensError::InvalidCredentials("Invalid username or recovery code".into())
+        });
+    };
+
+    let policy = password::current_policy(pool).await?;
+    let new_hash = password::hash(&request.new_password, &policy)?;
+
+    let mut tx = crate::db::begin_write(pool).await?;
+    // The used_at guard makes a code single-use even if two requests race
+    let consumed = sqlx::query("UPDATE recovery_codes SET used_at = $1 WHERE id = $2 AND used_at IS NULL")
+        .bind(now_secs())
+        .bind(code_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("consume recovery code"))?;
+    if consumed.rows_affected() == 0 {
+        return Err(DashlensError::InvalidCredentials("Invalid username or recovery code".into()));
+    }
+    sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+        .bind(&new_hash)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("update password"))?;
+    sqlx::query("DELETE FROM auth_tokens WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("revoke remember-me tokens"))?;
+    // Encrypted accounts: the code unwraps the data key, which is then
+    // re-wrapped under the new password
+    if vault::is_enabled(&mut tx, user_id).await? {
+        let key = wrapped_key
+            .map(|wrapped| vault::unwrap_key(&wrapped, &code))
+            .transpose()?
+            .flatten()
+            .ok_or_else(|| DashlensError::Internal("Recovery code cannot unlock the encrypted data".into()))?;
+        vault::store_key(&mut tx, user_id, &key, &request.new_password, &policy).await?;
+    }
+    let (remaining_codes,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM recovery_codes WHERE user_id = $1 AND used_at IS NULL")
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DashlensError::database("count recovery codes"))?;
+    let details = serde_json::json!({ "method": "recovery_code" });
+    audit::record(&mut tx, Some(user_id), &username, AuditEvent::PasswordChanged, details).await?;
+    tx.commit().await.map_err(DashlensError::database("update password"))?;
+
+    crate::throttle::clear_failures(pool, &username).await?;
+    Ok(RecoverAccountResponse { remaining_codes })
+}
+
+// Invalidate all existing codes and issue a new set; requires the password
+#[tauri::command]
+pub async fn regenerate_recovery_codes(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    request: RegenerateCodesRequest,
+) -> Result<Vec<String>> {
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let mut tx = crate::db::begin_write(&pool).await?;
+    vault::require_password(&mut tx, user_id, &request.password).await?;
+    let data_key = vault::unlock(&mut tx, user_id, &request.password).await?;
+    let codes = replace_codes(&mut tx, user_id, data_key.as_ref()).await?;
+    tx.commit().await.map_err(DashlensError::database("store recovery codes"))?;
+    Ok(codes)
+}
+
+// Number of unused codes the logged-in user has left
+#[tauri::command]
+pub async fn recovery_codes_remaining(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<i64> {
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM recovery_codes WHERE user_id = $1 AND used_at IS NULL")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .map_err(DashlensError::database("count recovery codes"))?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEW_PASSWORD: &str = "violet kettle harbor lantern";
+
+    // A registered account with cheap login hashing; codes are issued by the test
+    async fn pool() -> Pool<Sqlite> {
+        let pool = crate::db::memory_pool().await;
+        let cheap = Argon2Policy {
+            variant: Argon2Variant::Argon2id,
+            m_cost: 64,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        password::save_policy(&pool, &cheap).await.unwrap();
+        let password_hash = password::hash("old password", &cheap).unwrap();
+        sqlx::query("INSERT INTO users (id, username, password_hash) VALUES (1, 'driver', $1)")
+            .bind(&password_hash)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    fn request(code: &str) -> RecoverAccountRequest {
+        serde_json::from_value(serde_json::json!({
+            "username": "Driver",
+            "code": code,
+            "new_password": NEW_PASSWORD,
+        }))
+        .unwrap()
+    }
+
+    async fn issue(pool: &Pool<Sqlite>, data_key: Option<&DataKey>) -> Vec<String> {
+        let mut conn = pool.acquire().await.unwrap();
+        replace_codes(&mut conn, 1, data_key).await.unwrap()
+    }
+
+    #[test]
+    fn codes_are_single_use() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let codes = issue(&pool, None).await;
+            assert_eq!(codes.len(), CODE_COUNT);
+
+            // Typed in lower case without the dash
+            let typed = codes[3].replace('-', "").to_lowercase();
+            let response = recover(&pool, &request(&typed)).await.unwrap();
+            assert_eq!(response.remaining_codes, CODE_COUNT as i64 - 1);
+            let mut conn = pool.acquire().await.unwrap();
+            vault::require_password(&mut conn, 1, NEW_PASSWORD).await.unwrap();
+            drop(conn);
+
+            let reused = recover(&pool, &request(&codes[3])).await.unwrap_err();
+            assert_eq!(reused.code(), "invalid_credentials");
+        });
+    }
+
+    #[test]
+    fn regenerating_replaces_every_code() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let old = issue(&pool, None).await;
+            let new = issue(&pool, None).await;
+            assert!(old.iter().all(|code| !new.contains(code)));
+            let (stored,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM recovery_codes WHERE user_id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(stored, CODE_COUNT as i64);
+
+            let stale = recover(&pool, &request(&old[0])).await.unwrap_err();
+            assert_eq!(stale.code(), "invalid_credentials");
+            recover(&pool, &request(&new[0])).await.unwrap();
+        });
+    }
+
+    #[test]
+    fn attempts_are_throttled_like_logins() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let codes = issue(&pool, None).await;
+
+            for _ in 0..2 {
+                let error = recover(&pool, &request("AAAAA-AAAAA")).await.unwrap_err();
+                assert_eq!(error.code(), "invalid_credentials");
+            }
+            // The third failure starts the backoff, after which even a good
+            // code is turned away until the wait is over
+            let error = recover(&pool, &request("AAAAA-AAAAA")).await.unwrap_err();
+            assert_eq!(error.code(), "locked");
+            let error = recover(&pool, &request(&codes[0])).await.unwrap_err();
+            assert_eq!(error.code(), "locked");
+
+            // Unknown accounts are throttled the same way
+            let mut unknown = request("AAAAA-AAAAA");
+            unknown.username = "nobody".into();
+            for _ in 0..2 {
+                assert_eq!(recover(&pool, &unknown).await.unwrap_err().code(), "invalid_credentials");
+            }
+            assert_eq!(recover(&pool, &unknown).await.unwrap_err().code(), "locked");
+        });
+    }
+
+    // For an encrypted account the code unwraps the data key, which ends up
+    // wrapped under the new password
+    #[test]
+    fn recovery_keeps_the_data_key() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let key = DataKey::generate();
+            let policy = password::current_policy(&pool).await.unwrap();
+            let mut conn = pool.acquire().await.unwrap();
+            vault::store_key(&mut conn, 1, &key, "old password", &policy).await.unwrap();
+            drop(conn);
+            let codes = issue(&pool, Some(&key)).await;
+            let sealed = vault::seal_amount(Some(&key), Some(crate::money::Money::from_cents(4_321))).unwrap();
+
+            recover(&pool, &request(&codes[0])).await.unwrap();
+
+            let mut conn = pool.acquire().await.unwrap();
+            let unlocked = vault::unlock(&mut conn, 1, NEW_PASSWORD).await.unwrap().unwrap();
+            assert_eq!(
+                vault::open_amount(Some(&unlocked), sealed).unwrap(),
+                Some(crate::money::Money::from_cents(4_321))
+            );
+        });
+    }
+}
@@ -0,0 +1,324 @@
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Sqlite};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::auth::now_secs;
+use crate::error::{DashlensError, Result};
+
+// ---------------------------------------------------------------------------
+// Opt-in "remember me" tokens.
+//
+// A random token is written to TOKEN_FILE in the app data dir together with
+// an HMAC over its contents; the signing key lives in app_settings so a file
+// copied from another install or edited by hand is rejected. Only the
+// SHA-256 of the token is stored in `auth_tokens` (migration v5), so a leaked
+// database alone cannot be used to restore a session.
+// ---------------------------------------------------------------------------
+
+const TOKEN_FILE: &str = "remember.token";
+const TOKEN_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+const SIGNING_KEY_SETTING: &str = "remember_signing_key";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Serialize, Deserialize)]
+struct TokenFile {
+    user_id: i64,
+    token: String,
+    expires_at: i64,
+    signature: String,
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn token_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(TOKEN_FILE))
+        .map_err(|e| DashlensError::Internal(format!("Failed to resolve app data dir: {}", e)))
+}
+
+// Per-install HMAC key, generated on first use
+async fn signing_key(pool: &Pool<Sqlite>) -> Result<Vec<u8>> {
+    let key = match crate::settings::get(pool, SIGNING_KEY_SETTING).await? {
+        Some(key) => key,
+        None => {
+            let key = random_hex(32);
+            crate::settings::set(pool, SIGNING_KEY_SETTING, &key).await?;
+            key
+        }
+    };
+    hex::decode(key).map_err(|e| DashlensError::Internal(format!("Invalid remember-me signing key: {}", e)))
+}
+
+fn mac(key: &[u8], user_id: i64, token: &str, expires_at: i64) -> Result<HmacSha256> {
+    let mut mac = HmacSha256::new_from_slice(key)
+        .map_err(|e| DashlensError::Internal(format!("Invalid signing key: {}", e)))?;
+    mac.update(format!("{}:{}:{}", user_id, token, expires_at).as_bytes());
+    Ok(mac)
+}
+
+fn read_file(path: &Path) -> Result<Option<TokenFile>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(DashlensError::io("read remember-me token")(e)),
+    };
+    // A corrupt file is treated like a missing one and removed
+    match serde_json::from_str(&contents) {
+        Ok(file) => Ok(Some(file)),
+        Err(_) => {
+            remove_file(path)?;
+            Ok(None)
+        }
+    }
+}
+
+fn remove_file(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(DashlensError::io("remove remember-me token")(e)),
+    }
+}
+
+// Create a token for the user and persist it to the app data dir
+pub async fn issue<R: Runtime>(app: &AppHandle<R>, pool: &Pool<Sqlite>, user_id: i64) -> Result<()> {
+    issue_at(&token_path(app)?, pool, user_id).await
+}
+
+// Resolve the remembered user, if the token file is authentic and unexpired.
+// Any invalid token is revoked so it is not retried on the next start.
+pub async fn restore<R: Runtime>(app: &AppHandle<R>, pool: &Pool<Sqlite>) -> Result<Option<(i64, String)>> {
+    restore_at(&token_path(app)?, pool).await
+}
+
+// Delete the token file and its row, plus any expired tokens
+pub async fn revoke<R: Runtime>(app: &AppHandle<R>, pool: &Pool<Sqlite>) -> Result<()> {
+    revoke_at(&token_path(app)?, pool).await
+}
+
+// The above, for a token file at `path`
+async fn issue_at(path: &Path, pool: &Pool<Sqlite>, user_id: i64) -> Result<()> {
+    let token = random_hex(32);
+    let now = now_secs();
+    let expires_at = now + TOKEN_TTL_SECS;
+
+    sqlx::query(
+        "INSERT INTO auth_tokens (user_id, token_hash, created_at, expires_at)
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(user_id)
+    .bind(hash_token(&token))
+    .bind(now)
+    .bind(expires_at)
+    .execute(pool)
+    .await
+    .map_err(DashlensError::database("store remember-me token"))?;
+
+    let key = signing_key(pool).await?;
+    let signature = hex::encode(mac(&key, user_id, &token, expires_at)?.finalize().into_bytes());
+    let file = TokenFile {
+        user_id,
+        token,
+        expires_at,
+        signature,
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(DashlensError::io("create app data dir"))?;
+    }
+    let contents = serde_json::to_string(&file)
+        .map_err(|e| DashlensError::Internal(format!("Failed to encode remember-me token: {}", e)))?;
+    std::fs::write(path, contents).map_err(DashlensError::io("write remember-me token"))
+}
+
+async fn restore_at(path: &Path, pool: &Pool<Sqlite>) -> Result<Option<(i64, String)>> {
+    let Some(file) = read_file(path)? else {
+        return Ok(None);
+    };
+
+    let key = signing_key(pool).await?;
+    let signature = hex::decode(&file.signature).unwrap_or_default();
+    let authentic = mac(&key, file.user_id, &file.token, file.expires_at)?
+        .verify_slice(&signature)
+        .is_ok();
+
+    let user: Option<(i64, String)> = if authentic && file.expires_at > now_secs() {
+        sqlx::query_as(
+            "SELECT u.id, u.username
+               FROM auth_tokens t
+               JOIN users u ON u.id = t.user_id
+              WHERE t.token_hash = $1 AND t.user_id = $2 AND t.expires_at > $3",
+        )
+        .bind(hash_token(&file.token))
+        .bind(file.user_id)
+        .bind(now_secs())
+        .fetch_optional(pool)
+        .await
+        .map_err(DashlensError::database("look up remember-me token"))?
+    } else {
+        None
+    };
+
+    if user.is_none() {
+        revoke_at(path, pool).await?;
+    }
+    Ok(user)
+}
+
+async fn revoke_at(path: &Path, pool: &Pool<Sqlite>) -> Result<()> {
+    if let Some(file) = read_file(path)? {
+        sqlx::query("DELETE FROM auth_tokens WHERE token_hash = $1")
+            .bind(hash_token(&file.token))
+            .execute(pool)
+            .await
+            .map_err(DashlensError::database("revoke remember-me token"))?;
+    }
+    sqlx::query("DELETE FROM auth_tokens WHERE expires_at <= $1")
+        .bind(now_secs())
+        .execute(pool)
+        .await
+        .map_err(DashlensError::database("purge expired tokens"))?;
+    remove_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Token file in a directory of its own, removed again on drop
+    struct TokenDir(PathBuf);
+
+    impl TokenDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("dashlens-remember-{}-{}", name, random_hex(4)));
+            TokenDir(dir)
+        }
+
+        fn file(&self) -> PathBuf {
+            self.0.join(TOKEN_FILE)
+        }
+    }
+
+    impl Drop for TokenDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    async fn pool() -> Pool<Sqlite> {
+        let pool = crate::db::memory_pool().await;
+        sqlx::raw_sql(
+            "INSERT INTO users (id, username, password_hash) VALUES (1, 'driver', 'x'), (2, 'partner', 'x');",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    fn edit(path: &Path, change: impl FnOnce(&mut TokenFile)) {
+        let mut file: TokenFile = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        change(&mut file);
+        std::fs::write(path, serde_json::to_string(&file).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn issued_token_restores_and_only_its_hash_is_stored() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let dir = TokenDir::new("issue");
+            issue_at(&dir.file(), &pool, 1).await.unwrap();
+
+            let file: TokenFile = serde_json::from_str(&std::fs::read_to_string(dir.file()).unwrap()).unwrap();
+            let stored: Vec<String> = sqlx::query_scalar("SELECT token_hash FROM auth_tokens")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            assert_eq!(stored, [hash_token(&file.token)]);
+            assert_ne!(stored[0], file.token);
+
+            assert_eq!(restore_at(&dir.file(), &pool).await.unwrap(), Some((1, "driver".to_string())));
+        });
+    }
+
+    #[test]
+    fn tampered_file_is_rejected_and_revoked() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let tampered: [fn(&mut TokenFile); 3] = [
+                |file| file.user_id = 2,
+                |file| file.expires_at += 60,
+                |file| file.signature = "00".repeat(32),
+            ];
+            for (i, change) in tampered.into_iter().enumerate() {
+                let dir = TokenDir::new(&format!("tampered{}", i));
+                issue_at(&dir.file(), &pool, 1).await.unwrap();
+                edit(&dir.file(), change);
+
+                assert_eq!(restore_at(&dir.file(), &pool).await.unwrap(), None);
+                assert!(!dir.file().exists());
+            }
+            let (left,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM auth_tokens")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(left, 0);
+        });
+    }
+
+    #[test]
+    fn revoked_or_replaced_tokens_are_rejected() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let dir = TokenDir::new("revoke");
+
+            // Revoked: the file is gone and a saved copy no longer matches a row
+            issue_at(&dir.file(), &pool, 1).await.unwrap();
+            let copy = std::fs::read_to_string(dir.file()).unwrap();
+            revoke_at(&dir.file(), &pool).await.unwrap();
+            assert!(!dir.file().exists());
+            std::fs::write(dir.file(), &copy).unwrap();
+            assert_eq!(restore_at(&dir.file(), &pool).await.unwrap(), None);
+
+            // Replaced: a new login's token takes over; the old file is dead
+            issue_at(&dir.file(), &pool, 1).await.unwrap();
+            let old = std::fs::read_to_string(dir.file()).unwrap();
+            revoke_at(&dir.file(), &pool).await.unwrap();
+            issue_at(&dir.file(), &pool, 1).await.unwrap();
+            let current = std::fs::read_to_string(dir.file()).unwrap();
+            std::fs::write(dir.file(), &old).unwrap();
+            assert_eq!(restore_at(&dir.file(), &pool).await.unwrap(), None);
+
+            // Password changes and recovery delete every row for the user
+            std::fs::write(dir.file(), &current).unwrap();
+            sqlx::query("DELETE FROM auth_tokens WHERE user_id = 1").execute(&pool).await.unwrap();
+            assert_eq!(restore_at(&dir.file(), &pool).await.unwrap(), None);
+        });
+    }
+
+    // A token signed by another install's key does not verify here
+    #[test]
+    fn signing_key_is_per_install() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let dir = TokenDir::new("install");
+            issue_at(&dir.file(), &pool, 1).await.unwrap();
+            crate::settings::set(&pool, SIGNING_KEY_SETTING, &random_hex(32)).await.unwrap();
+            assert_eq!(restore_at(&dir.file(), &pool).await.unwrap(), None);
+        });
+    }
+}
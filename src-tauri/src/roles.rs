@@ -0,0 +1,302 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use tauri::State;
+use tauri_plugin_sql::DbInstances;
+
+use crate::audit::{self, AuditEvent};
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+use crate::password;
+use crate::secret::SecretString;
+
+// ---------------------------------------------------------------------------
+// Owners and viewers (`users.role` / `users.owner_id`, migration v12).
+//
+// Everyone who registers is an owner of their own data. An owner can add
+// viewer accounts — a partner, an accountant — that log in with their own
+// credentials and read the owner's sessions and analytics, including
+// exports, but are refused with `permission_denied` on anything that
+// creates, updates or deletes earnings data. A viewer's `owner_id` names
+// the owner whose data they see; for owners it is NULL.
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Owner,
+    Viewer,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Role> {
+        match value {
+            "owner" => Ok(Role::Owner),
+            "viewer" => Ok(Role::Viewer),
+            other => Err(DashlensError::Internal(format!("Unknown role '{}'", other))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateViewerRequest {
+    pub username: String,
+    pub password: SecretString,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Viewer {
+    pub user_id: i64,
+    pub username: String,
+    pub created_at: Option<String>,
+}
+
+// The user's role and whose data they work on (themselves, for owners)
+pub async fn lookup(pool: &Pool<Sqlite>, user_id: i64) -> Result<(Role, i64)> {
+    let (role, owner_id): (String, Option<i64>) = sqlx::query_as("SELECT role, owner_id FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(DashlensError::database("look up user role"))?;
+    let role = Role::parse(&role)?;
+    match (role, owner_id) {
+        (Role::Owner, _) => Ok((role, user_id)),
+        (Role::Viewer, Some(owner_id)) => Ok((role, owner_id)),
+        (Role::Viewer, None) => Err(DashlensError::Internal(format!("Viewer {} has no owner", user_id))),
+    }
+}
+
+pub async fn has_viewers(pool: &Pool<Sqlite>, owner_id: i64) -> Result<bool> {
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE owner_id = $1")
+        .bind(owner_id)
+        .fetch_one(pool)
+        .await
+        .map_err(DashlensError::database("count viewers"))?;
+    Ok(count > 0)
+}
+
+// ---------------------------------------------------------------------------
+// Commands (owners only)
+// ---------------------------------------------------------------------------
+
+// Add a viewer account that can read the logged-in owner's data
+#[tauri::command]
+pub async fn create_viewer(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    request: CreateViewerRequest,
+) -> Result<Viewer> {
+    let pool = crate::db::pool(&db).await?;
+    add_viewer(&pool, &user, &request).await
+}
+
+async fn add_viewer(pool: &Pool<Sqlite>, user: &CurrentUser, request: &CreateViewerRequest) -> Result<Viewer> {
+    user.require_owner()?;
+    let username = crate::username::validate(&request.username)?;
+    crate::password_policy::enforce(&request.password, &username)?;
+
+    // A viewer has no way to unwrap the owner's data key
+    if user.data_key.is_some() {
+        return Err(DashlensError::Conflict(
+            "Viewers cannot be added while earnings encryption is enabled".into(),
+        ));
+    }
+
+    let policy = password::current_policy(pool).await?;
+    let password_hash = password::hash(&request.password, &policy)?;
+
+    // Checked under the write lock so a racing registration cannot take the
+    // name in between
+    let mut tx = crate::db::begin_write(pool).await?;
+    let existing: Option<(i64,)> = sqlx::query_as("SELECT id FROM users WHERE username = $1 COLLATE NOCASE")
+        .bind(&username)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(DashlensError::database("look up user"))?;
+    if existing.is_some() {
+        return Err(DashlensError::Conflict("Username already exists".into()));
+    }
+    let result = sqlx::query("INSERT INTO users (username, password_hash, role, owner_id) VALUES ($1, $2, $3, $4)")
+        .bind(&username)
+        .bind(&password_hash)
+        .bind(Role::Viewer.as_str())
+        .bind(user.user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("create viewer"))?;
+    let viewer_id = result.last_insert_rowid();
+
+    let details = serde_json::json!({ "viewer_id": viewer_id, "viewer": username });
+    audit::record(&mut tx, Some(user.user_id), &user.username, AuditEvent::ViewerAdded, details).await?;
+    tx.commit().await.map_err(DashlensError::database("create viewer"))?;
+
+    let viewer = sqlx::query_as("SELECT id AS user_id, username, created_at FROM users WHERE id = $1")
+        .bind(viewer_id)
+        .fetch_one(pool)
+        .await
+        .map_err(DashlensError::database("load viewer"))?;
+    Ok(viewer)
+}
+
+#[tauri::command]
+pub async fn list_viewers(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<Vec<Viewer>> {
+    user.require_owner()?;
+    let pool = crate::db::pool(&db).await?;
+    sqlx::query_as("SELECT id AS user_id, username, created_at FROM users WHERE owner_id = $1 ORDER BY username")
+        .bind(user.user_id)
+        .fetch_all(&pool)
+        .await
+        .map_err(DashlensError::database("list viewers"))
+}
+
+// Delete one of the owner's viewer accounts and everything tied to it
+#[tauri::command]
+pub async fn remove_viewer(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    viewer_id: i64,
+) -> Result<()> {
+    user.require_owner()?;
+    let pool = crate::db::pool(&db).await?;
+
+    let mut tx = crate::db::begin_write(&pool).await?;
+    let viewer: Option<(String,)> = sqlx::query_as("SELECT username FROM users WHERE id = $1 AND owner_id = $2")
+        .bind(viewer_id)
+        .bind(user.user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(DashlensError::database("look up viewer"))?;
+    let Some((viewer_name,)) = viewer else {
+        return Err(DashlensError::NotFound(format!("Viewer {} not found", viewer_id)));
+    };
+
+    crate::auth::delete_user_rows(&mut tx, viewer_id, &viewer_name).await?;
+
+    let details = serde_json::json!({ "viewer_id": viewer_id, "viewer": viewer_name });
+    audit::record(&mut tx, Some(user.user_id), &user.username, AuditEvent::ViewerRemoved, details).await?;
+    tx.commit().await.map_err(DashlensError::database("remove viewer"))?;
+    crate::profiles::forget(&pool, viewer_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::password::{Argon2Policy, Argon2Variant};
+
+    const OWNER: i64 = 1;
+    const VIEWER: i64 = 2;
+    const OTHER_OWNER: i64 = 3;
+
+    // An owner with one viewer, another owner, and a session each
+    async fn pool() -> Pool<Sqlite> {
+        let pool = crate::db::memory_pool().await;
+        let cheap = Argon2Policy {
+            variant: Argon2Variant::Argon2id,
+            m_cost: 64,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        password::save_policy(&pool, &cheap).await.unwrap();
+        sqlx::raw_sql(
+            "INSERT INTO users (id, username, password_hash, role, owner_id) VALUES
+                 (1, 'owner', 'x', 'owner', NULL),
+                 (2, 'partner', 'x', 'viewer', 1),
+                 (3, 'stranger', 'x', 'owner', NULL);
+             INSERT INTO sessions (user_id, date, total_earnings) VALUES
+                 (1, '2024-03-01', 2500), (3, '2024-03-02', 4000);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    async fn current_user(pool: &Pool<Sqlite>, user_id: i64, data_key: Option<crate::vault::DataKey>) -> CurrentUser {
+        let (role, owner_id) = lookup(pool, user_id).await.unwrap();
+        CurrentUser {
+            user_id,
+            username: format!("user{}", user_id),
+            role,
+            owner_id,
+            data_key,
+        }
+    }
+
+    fn request(username: &str) -> CreateViewerRequest {
+        CreateViewerRequest {
+            username: username.into(),
+            password: serde_json::from_str("\"correct horse battery staple\"").unwrap(),
+        }
+    }
+
+    #[test]
+    fn viewers_read_their_owners_data() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            assert_eq!(lookup(&pool, OWNER).await.unwrap(), (Role::Owner, OWNER));
+            assert_eq!(lookup(&pool, VIEWER).await.unwrap(), (Role::Viewer, OWNER));
+            assert_eq!(lookup(&pool, OTHER_OWNER).await.unwrap(), (Role::Owner, OTHER_OWNER));
+            assert!(has_viewers(&pool, OWNER).await.unwrap());
+            assert!(!has_viewers(&pool, OTHER_OWNER).await.unwrap());
+
+            // Data queries scope by owner_id, so the viewer sees the owner's
+            // sessions and nobody else's
+            let viewer = current_user(&pool, VIEWER, None).await;
+            let dates: Vec<String> = sqlx::query_scalar("SELECT date FROM sessions WHERE user_id = $1")
+                .bind(viewer.owner_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            assert_eq!(dates, ["2024-03-01"]);
+
+            sqlx::query("UPDATE users SET owner_id = NULL WHERE id = $1")
+                .bind(VIEWER)
+                .execute(&pool)
+                .await
+                .unwrap();
+            assert_eq!(lookup(&pool, VIEWER).await.unwrap_err().code(), "internal");
+        });
+    }
+
+    #[test]
+    fn viewers_cannot_write() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let owner = current_user(&pool, OWNER, None).await;
+            let viewer = current_user(&pool, VIEWER, None).await;
+            assert!(owner.require_owner().is_ok());
+            assert_eq!(viewer.require_owner().unwrap_err().code(), "permission_denied");
+
+            // Nor can they add viewers of their own
+            let error = add_viewer(&pool, &viewer, &request("accountant")).await.unwrap_err();
+            assert_eq!(error.code(), "permission_denied");
+        });
+    }
+
+    #[test]
+    fn owners_add_viewers_unless_encrypted() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let encrypted = current_user(&pool, OWNER, Some(crate::vault::DataKey::generate())).await;
+            let error = add_viewer(&pool, &encrypted, &request("accountant")).await.unwrap_err();
+            assert_eq!(error.code(), "conflict");
+
+            let owner = current_user(&pool, OWNER, None).await;
+            let error = add_viewer(&pool, &owner, &request("Partner")).await.unwrap_err();
+            assert_eq!(error.code(), "conflict");
+
+            let added = add_viewer(&pool, &owner, &request("Accountant")).await.unwrap();
+            assert_eq!(added.username, "accountant");
+            assert_eq!(lookup(&pool, added.user_id).await.unwrap(), (Role::Viewer, OWNER));
+        });
+    }
+}
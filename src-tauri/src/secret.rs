@@ -0,0 +1,74 @@
+use serde::Deserialize;
+use std::fmt;
+use std::ops::Deref;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+// ---------------------------------------------------------------------------
+// Plaintext secrets (passwords, recovery codes) received from the frontend.
+//
+// The buffer is zeroed when the value is dropped, and Debug never prints
+// it, so request structs can keep deriving Debug. Derefs to `&str` for the
+// hashing and key-derivation calls that need the plaintext.
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Deserialize, Zeroize, ZeroizeOnDrop)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{ChangePasswordRequest, DeleteAccountRequest, LoginRequest, RegisterRequest, SetPinRequest};
+    use crate::recovery::RecoverAccountRequest;
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
+
+    const PLAINTEXT: &str = "hunter2-correct-horse";
+
+    fn assert_redacted<T: DeserializeOwned + fmt::Debug>(request: serde_json::Value) {
+        let value: T = serde_json::from_value(request).expect("request deserializes");
+        let debug = format!("{:?}", value);
+        assert!(!debug.contains(PLAINTEXT), "plaintext leaked: {}", debug);
+        assert!(debug.contains("<redacted>"), "no redaction marker: {}", debug);
+    }
+
+    #[test]
+    fn debug_never_prints_the_secret() {
+        let secret = SecretString(PLAINTEXT.into());
+        assert_eq!(format!("{:?}", secret), "SecretString(<redacted>)");
+        assert_eq!(&*secret, PLAINTEXT);
+    }
+
+    // VerifyPasswordRequest went away with the verify_password command; these
+    // are the request types that still carry plaintext
+    #[test]
+    fn requests_with_secrets_stay_redacted() {
+        assert_redacted::<LoginRequest>(json!({ "username": "driver", "password": PLAINTEXT }));
+        assert_redacted::<RegisterRequest>(json!({ "username": "driver", "password": PLAINTEXT }));
+        assert_redacted::<DeleteAccountRequest>(json!({ "password": PLAINTEXT }));
+        assert_redacted::<ChangePasswordRequest>(json!({
+            "current_password": PLAINTEXT,
+            "new_password": PLAINTEXT,
+        }));
+        assert_redacted::<SetPinRequest>(json!({ "password": PLAINTEXT, "pin": PLAINTEXT }));
+        assert_redacted::<RecoverAccountRequest>(json!({
+            "username": "driver",
+            "code": PLAINTEXT,
+            "new_password": PLAINTEXT,
+        }));
+    }
+}
@@ -0,0 +1,653 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::cmp::Ordering;
+use tauri::State;
+use tauri_plugin_sql::DbInstances;
+
+use crate::entries::{check_date, check_time, Session, SessionRow, SESSION_COLUMNS};
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+use crate::money::Money;
+use crate::vault::DataKey;
+
+// ---------------------------------------------------------------------------
+// Filtered, sorted and paginated session listing for the Data table.
+//
+// For plain accounts SQLite does all of it: filters, sort, the page cut and
+// one aggregate query for the totals. Encrypted earnings (vault.rs) cannot
+// be compared in SQL, so for those accounts SQLite only narrows the rows by
+// date range, weekday and store, and the earnings bounds, time-of-day
+// window, sorting, totals and page cut happen here after decoding. Both
+// paths order and filter the same way. Pages are keyset-paginated: the
+// cursor is the sort value and id of the last row returned, so rows added or
+// removed between requests do not shift later pages.
+// ---------------------------------------------------------------------------
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct SessionFilter {
+    pub date_from: Option<String>, // inclusive, "YYYY-MM-DD"
+    pub date_to: Option<String>,   // inclusive
+    pub min_earnings: Option<Money>,
+    pub max_earnings: Option<Money>,
+    pub store: Option<String>,     // case-insensitive substring of any offer's store
+    pub weekdays: Vec<u8>,         // 0 = Sunday … 6 = Saturday; empty = every day
+    pub start_from: Option<String>, // "HH:MM"; window on start_time, may wrap midnight
+    pub start_to: Option<String>,   // exclusive
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionSort {
+    #[default]
+    DateDesc,
+    DateAsc,
+    EarningsDesc,
+    EarningsAsc,
+    DurationDesc,
+    DurationAsc,
+}
+
+impl SessionSort {
+    fn descending(self) -> bool {
+        matches!(self, SessionSort::DateDesc | SessionSort::EarningsDesc | SessionSort::DurationDesc)
+    }
+
+    fn value(self, session: &Session) -> Option<SortValue> {
+        match self {
+            SessionSort::DateDesc | SessionSort::DateAsc => Some(SortValue::Text(format!(
+                "{} {}",
+                session.date,
+                session.start_time.as_deref().unwrap_or("")
+            ))),
+            SessionSort::EarningsDesc | SessionSort::EarningsAsc => {
+                session.total_earnings.map(|earnings| SortValue::Number(earnings.cents()))
+            }
+            SessionSort::DurationDesc | SessionSort::DurationAsc => session.total_time.map(SortValue::Number),
+        }
+    }
+}
+
+impl SessionSort {
+    // SQL for `value`: NULLs sort first ascending and last descending, as
+    // `compare_values` has them
+    fn sql_value(self) -> &'static str {
+        match self {
+            SessionSort::DateDesc | SessionSort::DateAsc => "(date || ' ' || COALESCE(start_time, ''))",
+            SessionSort::EarningsDesc | SessionSort::EarningsAsc => "total_earnings",
+            SessionSort::DurationDesc | SessionSort::DurationAsc => "total_time",
+        }
+    }
+
+    // Rows strictly after the cursor ($10 = value, $11 = id; $12 = 0 for the
+    // first page)
+    fn sql_after_cursor(self) -> String {
+        let value = self.sql_value();
+        if self.descending() {
+            format!(
+                "($12 = 0
+                  OR ($10 IS NULL AND {value} IS NULL AND id < $11)
+                  OR ($10 IS NOT NULL AND ({value} IS NULL OR {value} < $10 OR ({value} = $10 AND id < $11))))"
+            )
+        } else {
+            format!(
+                "($12 = 0
+                  OR ($10 IS NULL AND ({value} IS NOT NULL OR id > $11))
+                  OR ($10 IS NOT NULL AND ({value} > $10 OR ({value} = $10 AND id > $11))))"
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SortValue {
+    Number(i64), // cents or minutes
+    Text(String),
+}
+
+// Opaque to the frontend: pass `next_cursor` back as `cursor` for the next page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCursor {
+    pub value: Option<SortValue>,
+    pub id: i64,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct SessionTotals {
+    pub count: i64,
+    pub total_earnings: Money,
+    pub base_pay: Money,
+    pub tips: Money,
+    pub active_time: i64, // minutes
+    pub total_time: i64,  // minutes
+    pub deliveries: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionPage {
+    pub sessions: Vec<Session>,
+    pub next_cursor: Option<SessionCursor>,
+    pub totals: SessionTotals, // over every matching session, not just this page
+}
+
+impl SessionTotals {
+    fn add(&mut self, session: &Session) -> Result<()> {
+        let add = |total: Money, amount: Option<Money>| {
+            total
+                .checked_add(amount.unwrap_or_default())
+                .ok_or_else(|| DashlensError::Internal("Earnings total is out of range".into()))
+        };
+        self.count += 1;
+        self.total_earnings = add(self.total_earnings, session.total_earnings)?;
+        self.base_pay = add(self.base_pay, session.base_pay)?;
+        self.tips = add(self.tips, session.tips)?;
+        self.active_time += session.active_time.unwrap_or(0);
+        self.total_time += session.total_time.unwrap_or(0);
+        self.deliveries += session.deliveries.unwrap_or(0);
+        Ok(())
+    }
+}
+
+impl SessionFilter {
+    fn validate(&self) -> Result<()> {
+        for date in [&self.date_from, &self.date_to].into_iter().flatten() {
+            check_date(date)?;
+        }
+        check_time("Start of the time window", &self.start_from)?;
+        check_time("End of the time window", &self.start_to)?;
+        if self.weekdays.iter().any(|day| *day > 6) {
+            return Err(DashlensError::Validation("Weekdays must be 0 (Sunday) to 6 (Saturday)".into()));
+        }
+        if let (Some(min), Some(max)) = (self.min_earnings, self.max_earnings) {
+            if min > max {
+                return Err(DashlensError::Validation(
+                    "Minimum earnings cannot be above maximum earnings".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Weekdays as the digit string SQLite's strftime('%w') is looked up in
+    fn weekday_digits(&self) -> Option<String> {
+        if self.weekdays.is_empty() {
+            None
+        } else {
+            Some(self.weekdays.iter().map(|day| day.to_string()).collect())
+        }
+    }
+
+    // LIKE pattern for the store substring, with wildcards in the input escaped
+    fn store_pattern(&self) -> Option<String> {
+        let store = self.store.as_deref().map(str::trim).filter(|store| !store.is_empty())?;
+        let escaped = store.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        Some(format!("%{}%", escaped))
+    }
+
+    // The filters applied after decoding
+    fn matches(&self, session: &Session) -> bool {
+        let earnings_ok = match (self.min_earnings, self.max_earnings) {
+            (None, None) => true,
+            (min, max) => session.total_earnings.is_some_and(|earnings| {
+                !matches!(min, Some(min) if earnings < min) && !matches!(max, Some(max) if earnings > max)
+            }),
+        };
+        earnings_ok && self.in_time_window(session.start_time.as_deref())
+    }
+
+    fn in_time_window(&self, start_time: Option<&str>) -> bool {
+        let (from, to) = (self.start_from.as_deref(), self.start_to.as_deref());
+        if from.is_none() && to.is_none() {
+            return true;
+        }
+        let Some(start) = start_time else { return false };
+        match (from, to) {
+            (Some(from), Some(to)) if from > to => start >= from || start < to, // wraps midnight
+            (from, to) => !matches!(from, Some(from) if start < from) && !matches!(to, Some(to) if start >= to),
+        }
+    }
+}
+
+fn compare_values(a: &Option<SortValue>, b: &Option<SortValue>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(SortValue::Number(a)), Some(SortValue::Number(b))) => a.cmp(b),
+        (Some(SortValue::Text(a)), Some(SortValue::Text(b))) => a.cmp(b),
+        (Some(SortValue::Number(_)), Some(SortValue::Text(_))) => Ordering::Less,
+        (Some(SortValue::Text(_)), Some(SortValue::Number(_))) => Ordering::Greater,
+    }
+}
+
+// Position of (value, id) in the requested order; ids break ties
+fn compare(sort: SessionSort, a: (&Option<SortValue>, i64), b: (&Option<SortValue>, i64)) -> Ordering {
+    let ordering = compare_values(a.0, b.0).then(a.1.cmp(&b.1));
+    if sort.descending() {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+// Filters pushed into SQL. $1 = owner, $2/$3 = date range, $4 = weekday
+// digits, $5 = store pattern; PLAIN_FILTERS adds $6/$7 = earnings bounds in
+// cents and $8/$9 = start-time window, for unencrypted amounts only.
+const FILTERS: &str = "user_id = $1
+    AND ($2 IS NULL OR date >= $2)
+    AND ($3 IS NULL OR date <= $3)
+    AND ($4 IS NULL OR instr($4, strftime('%w', date)) > 0)
+    AND ($5 IS NULL OR EXISTS (
+          SELECT 1 FROM offers
+           WHERE offers.session_id = sessions.id
+             AND offers.store LIKE $5 ESCAPE '\\'))";
+
+const PLAIN_FILTERS: &str = "($6 IS NULL OR total_earnings >= $6)
+    AND ($7 IS NULL OR total_earnings <= $7)
+    AND (($8 IS NULL AND $9 IS NULL)
+         OR (start_time IS NOT NULL AND CASE
+               WHEN $8 > $9 THEN start_time >= $8 OR start_time < $9
+               ELSE ($8 IS NULL OR start_time >= $8) AND ($9 IS NULL OR start_time < $9)
+             END))";
+
+// One page (every remaining row when `limit` is None) of a plain account's
+// sessions, read straight from SQL
+async fn select_page(
+    pool: &Pool<Sqlite>,
+    owner_id: i64,
+    filter: &SessionFilter,
+    sort: SessionSort,
+    cursor: Option<&SessionCursor>,
+    limit: Option<usize>,
+) -> Result<(Vec<Session>, Option<SessionCursor>)> {
+    let direction = if sort.descending() { "DESC" } else { "ASC" };
+    let sql = format!(
+        "SELECT {} FROM sessions
+          WHERE {} AND {} AND {}
+          ORDER BY {} {direction}, id {direction}
+          LIMIT $13",
+        SESSION_COLUMNS,
+        FILTERS,
+        PLAIN_FILTERS,
+        sort.sql_after_cursor(),
+        sort.sql_value(),
+    );
+    let query = sqlx::query_as::<_, SessionRow>(&sql)
+        .bind(owner_id)
+        .bind(&filter.date_from)
+        .bind(&filter.date_to)
+        .bind(filter.weekday_digits())
+        .bind(filter.store_pattern())
+        .bind(filter.min_earnings.map(Money::cents))
+        .bind(filter.max_earnings.map(Money::cents))
+        .bind(&filter.start_from)
+        .bind(&filter.start_to);
+    let query = match cursor.and_then(|cursor| cursor.value.clone()) {
+        Some(SortValue::Number(value)) => query.bind(Some(value)),
+        Some(SortValue::Text(value)) => query.bind(value),
+        None => query.bind(None::<i64>),
+    };
+    // One extra row tells whether another page follows; -1 is no limit
+    let fetch = limit.map_or(-1, |limit| limit as i64 + 1);
+    let rows = query
+        .bind(cursor.map_or(0, |cursor| cursor.id))
+        .bind(cursor.is_some())
+        .bind(fetch)
+        .fetch_all(pool)
+        .await
+        .map_err(DashlensError::database("query sessions"))?;
+
+    let mut sessions = rows.into_iter().map(|row| row.open(None)).collect::<Result<Vec<_>>>()?;
+    let next_cursor = match limit {
+        Some(limit) if sessions.len() > limit => {
+            sessions.truncate(limit);
+            sessions.last().map(|session| SessionCursor {
+                value: sort.value(session),
+                id: session.id,
+            })
+        }
+        _ => None,
+    };
+    Ok((sessions, next_cursor))
+}
+
+// Totals over every plain session matching `filter`, in one aggregate query
+async fn select_totals(pool: &Pool<Sqlite>, owner_id: i64, filter: &SessionFilter) -> Result<SessionTotals> {
+    let sql = format!(
+        "SELECT COUNT(*),
+                COALESCE(SUM(total_earnings), 0), COALESCE(SUM(base_pay), 0), COALESCE(SUM(tips), 0),
+                COALESCE(SUM(active_time), 0), COALESCE(SUM(total_time), 0), COALESCE(SUM(deliveries), 0)
+           FROM sessions
+          WHERE {} AND {}",
+        FILTERS, PLAIN_FILTERS
+    );
+    let (count, total_earnings, base_pay, tips, active_time, total_time, deliveries): (i64, i64, i64, i64, i64, i64, i64) =
+        sqlx::query_as(&sql)
+            .bind(owner_id)
+            .bind(&filter.date_from)
+            .bind(&filter.date_to)
+            .bind(filter.weekday_digits())
+            .bind(filter.store_pattern())
+            .bind(filter.min_earnings.map(Money::cents))
+            .bind(filter.max_earnings.map(Money::cents))
+            .bind(&filter.start_from)
+            .bind(&filter.start_to)
+            .fetch_one(pool)
+            .await
+            .map_err(DashlensError::database("total sessions"))?;
+    Ok(SessionTotals {
+        count,
+        total_earnings: Money::from_cents(total_earnings),
+        base_pay: Money::from_cents(base_pay),
+        tips: Money::from_cents(tips),
+        active_time,
+        total_time,
+        deliveries,
+    })
+}
+
+// The same page worked out after decoding, for encrypted accounts: SQL
+// narrows what it can, the rest is filtered, totalled and sorted here
+async fn decode_page(
+    pool: &Pool<Sqlite>,
+    owner_id: i64,
+    key: Option<&DataKey>,
+    filter: &SessionFilter,
+    sort: SessionSort,
+    cursor: Option<&SessionCursor>,
+    limit: Option<usize>,
+) -> Result<SessionPage> {
+    let sql = format!("SELECT {} FROM sessions WHERE {}", SESSION_COLUMNS, FILTERS);
+    let rows: Vec<SessionRow> = sqlx::query_as(&sql)
+        .bind(owner_id)
+        .bind(&filter.date_from)
+        .bind(&filter.date_to)
+        .bind(filter.weekday_digits())
+        .bind(filter.store_pattern())
+        .fetch_all(pool)
+        .await
+        .map_err(DashlensError::database("query sessions"))?;
+
+    let mut matching = Vec::new();
+    let mut totals = SessionTotals::default();
+    for row in rows {
+        let session = row.open(key)?;
+        if !filter.matches(&session) {
+            continue;
+        }
+        totals.add(&session)?;
+        matching.push((sort.value(&session), session));
+    }
+
+    matching.sort_by(|a, b| compare(sort, (&a.0, a.1.id), (&b.0, b.1.id)));
+
+    let start = match cursor {
+        Some(cursor) => matching
+            .iter()
+            .position(|(value, session)| {
+                compare(sort, (value, session.id), (&cursor.value, cursor.id)) == Ordering::Greater
+            })
+            .unwrap_or(matching.len()),
+        None => 0,
+    };
+    let end = limit.map_or(matching.len(), |limit| (start + limit).min(matching.len()));
+    let next_cursor = if end < matching.len() {
+        matching.get(end - 1).map(|(value, session)| SessionCursor {
+            value: value.clone(),
+            id: session.id,
+        })
+    } else {
+        None
+    };
+    let sessions = matching.drain(start..end).map(|(_, session)| session).collect();
+
+    Ok(SessionPage {
+        sessions,
+        next_cursor,
+        totals,
+    })
+}
+
+async fn run_query(
+    pool: &Pool<Sqlite>,
+    user: &CurrentUser,
+    filter: &SessionFilter,
+    sort: SessionSort,
+    cursor: Option<&SessionCursor>,
+    limit: Option<usize>,
+) -> Result<SessionPage> {
+    match &user.data_key {
+        Some(key) => decode_page(pool, user.owner_id, Some(key), filter, sort, cursor, limit).await,
+        None => {
+            let (sessions, next_cursor) = select_page(pool, user.owner_id, filter, sort, cursor, limit).await?;
+            let totals = select_totals(pool, user.owner_id, filter).await?;
+            Ok(SessionPage {
+                sessions,
+                next_cursor,
+                totals,
+            })
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn query_sessions(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    filter: Option<SessionFilter>,
+    sort: Option<SessionSort>,
+    cursor: Option<SessionCursor>,
+    limit: Option<usize>,
+) -> Result<SessionPage> {
+    let filter = filter.unwrap_or_default();
+    filter.validate()?;
+    let sort = sort.unwrap_or_default();
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let pool = crate::db::pool(&db).await?;
+    run_query(&pool, &user, &filter, sort, cursor.as_ref(), Some(limit)).await
+}
+
+// Every session matching `filter` in one go (CSV export), rather than paging
+// through query_sessions and re-reading the table for each page
+#[tauri::command]
+pub async fn query_all_sessions(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    filter: Option<SessionFilter>,
+    sort: Option<SessionSort>,
+) -> Result<Vec<Session>> {
+    let filter = filter.unwrap_or_default();
+    filter.validate()?;
+    let pool = crate::db::pool(&db).await?;
+    let page = run_query(&pool, &user, &filter, sort.unwrap_or_default(), None, None).await?;
+    Ok(page.sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: i64 = 1;
+    const SORTS: [SessionSort; 6] = [
+        SessionSort::DateDesc,
+        SessionSort::DateAsc,
+        SessionSort::EarningsDesc,
+        SessionSort::EarningsAsc,
+        SessionSort::DurationDesc,
+        SessionSort::DurationAsc,
+    ];
+
+    // Every migration applied, then sessions and offers with repeated sort
+    // values, NULLs and late-night starts, plus another user's rows
+    async fn pool() -> Pool<Sqlite> {
+        let pool = crate::db::memory_pool().await;
+        sqlx::raw_sql("INSERT INTO users (id, username, password_hash) VALUES (1, 'driver', 'x'), (2, 'other', 'x');")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        for i in 0..60i64 {
+            let user_id = if i % 13 == 5 { 2 } else { OWNER };
+            let date = format!("2024-03-{:02}", 1 + i % 20);
+            let start_time = (i % 7 != 3).then(|| format!("{:02}:{:02}", (i * 5) % 24, (i * 17) % 60));
+            let earnings = (i % 9 != 4).then_some(1_000 + (i % 6) * 250);
+            let total_time = (i % 8 != 2).then_some(30 + (i % 5) * 15);
+            sqlx::query(
+                "INSERT INTO sessions (user_id, date, start_time, total_time, active_time, deliveries,
+                                       total_earnings, base_pay, tips)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            )
+            .bind(user_id)
+            .bind(&date)
+            .bind(&start_time)
+            .bind(total_time)
+            .bind(total_time.map(|total| total - 10))
+            .bind(i % 4)
+            .bind(earnings)
+            .bind(earnings.map(|earnings| earnings - 300))
+            .bind(earnings.map(|_| 300))
+            .execute(&pool)
+            .await
+            .unwrap();
+            let store = ["Taco Spot", "Burger_Barn", "100% Juice", "Noodle House"][(i % 4) as usize];
+            sqlx::query("INSERT INTO offers (session_id, store) VALUES ($1, $2)")
+                .bind(i + 1)
+                .bind(store)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        pool
+    }
+
+    fn filters() -> Vec<SessionFilter> {
+        vec![
+            SessionFilter::default(),
+            SessionFilter {
+                date_from: Some("2024-03-05".into()),
+                date_to: Some("2024-03-15".into()),
+                weekdays: vec![1, 2, 6],
+                ..Default::default()
+            },
+            SessionFilter {
+                min_earnings: Some(Money::from_cents(1_250)),
+                max_earnings: Some(Money::from_cents(1_750)),
+                ..Default::default()
+            },
+            SessionFilter {
+                min_earnings: Some(Money::from_cents(1_500)),
+                store: Some("taco".into()),
+                ..Default::default()
+            },
+            // Wildcards in the store name are literal
+            SessionFilter {
+                store: Some("100%".into()),
+                ..Default::default()
+            },
+            SessionFilter {
+                store: Some("r_b".into()),
+                ..Default::default()
+            },
+            // Window across midnight, then an ordinary one
+            SessionFilter {
+                start_from: Some("22:00".into()),
+                start_to: Some("04:30".into()),
+                ..Default::default()
+            },
+            SessionFilter {
+                start_from: Some("08:00".into()),
+                start_to: Some("14:00".into()),
+                max_earnings: Some(Money::from_cents(1_500)),
+                ..Default::default()
+            },
+        ]
+    }
+
+    // Ids of every page, following next_cursor, plus the totals
+    async fn walk(
+        pool: &Pool<Sqlite>,
+        filter: &SessionFilter,
+        sort: SessionSort,
+        limit: usize,
+        plain: bool,
+    ) -> (Vec<i64>, String) {
+        let mut ids = Vec::new();
+        let mut cursor: Option<SessionCursor> = None;
+        loop {
+            let page = if plain {
+                let (sessions, next_cursor) =
+                    select_page(pool, OWNER, filter, sort, cursor.as_ref(), Some(limit)).await.unwrap();
+                let totals = select_totals(pool, OWNER, filter).await.unwrap();
+                SessionPage {
+                    sessions,
+                    next_cursor,
+                    totals,
+                }
+            } else {
+                decode_page(pool, OWNER, None, filter, sort, cursor.as_ref(), Some(limit)).await.unwrap()
+            };
+            assert!(page.sessions.len() <= limit);
+            ids.extend(page.sessions.iter().map(|session| session.id));
+            // Round-trip the cursor the way the frontend does
+            cursor = page
+                .next_cursor
+                .map(|cursor| serde_json::from_value(serde_json::to_value(cursor).unwrap()).unwrap());
+            if cursor.is_none() {
+                return (ids, format!("{:?}", page.totals));
+            }
+        }
+    }
+
+    #[test]
+    fn sql_pages_match_the_decoded_path() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            for filter in filters() {
+                for sort in SORTS {
+                    let (expected, expected_totals) = walk(&pool, &filter, sort, 1_000, false).await;
+                    assert!(!expected.is_empty(), "{:?} matches nothing", filter);
+                    for limit in [1, 4, 7] {
+                        let (ids, totals) = walk(&pool, &filter, sort, limit, true).await;
+                        assert_eq!(ids, expected, "{:?} {:?} limit {}", filter, sort, limit);
+                        assert_eq!(totals, expected_totals, "{:?}", filter);
+                        assert_eq!(walk(&pool, &filter, sort, limit, false).await.0, expected);
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn filters_and_order_hold() {
+        tauri::async_runtime::block_on(async {
+            let pool = pool().await;
+            let filter = SessionFilter {
+                min_earnings: Some(Money::from_cents(1_250)),
+                ..Default::default()
+            };
+            let (sessions, next_cursor) =
+                select_page(&pool, OWNER, &filter, SessionSort::EarningsDesc, None, None).await.unwrap();
+            assert!(next_cursor.is_none());
+            assert!(!sessions.is_empty());
+            let earnings: Vec<i64> = sessions.iter().map(|s| s.total_earnings.unwrap().cents()).collect();
+            assert!(earnings.windows(2).all(|pair| pair[0] >= pair[1]));
+            assert!(earnings.iter().all(|cents| *cents >= 1_250));
+
+            // Unknown earnings sort last when descending, first when ascending
+            let all = SessionFilter::default();
+            let (desc, _) = select_page(&pool, OWNER, &all, SessionSort::EarningsDesc, None, None).await.unwrap();
+            let (asc, _) = select_page(&pool, OWNER, &all, SessionSort::EarningsAsc, None, None).await.unwrap();
+            assert!(desc.last().unwrap().total_earnings.is_none());
+            assert!(asc.first().unwrap().total_earnings.is_none());
+
+            let totals = select_totals(&pool, OWNER, &all).await.unwrap();
+            assert_eq!(totals.count, desc.len() as i64);
+            let sum: i64 = desc.iter().filter_map(|s| s.total_earnings).map(Money::cents).sum();
+            assert_eq!(totals.total_earnings, Money::from_cents(sum));
+        });
+    }
+}
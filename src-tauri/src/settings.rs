@@ -0,0 +1,31 @@
+use sqlx::SqliteExecutor;
+
+use crate::error::{DashlensError, Result};
+
+// ---------------------------------------------------------------------------
+// Key/value app settings stored in the `app_settings` table (migration v5).
+// Values are TEXT; callers own their own encoding. Both functions take a
+// pool or, for read-modify-write, a connection inside `db::begin_write`.
+// ---------------------------------------------------------------------------
+
+pub async fn get<'e>(executor: impl SqliteExecutor<'e>, key: &str) -> Result<Option<String>> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT value FROM app_settings WHERE key = $1")
+        .bind(key)
+        .fetch_optional(executor)
+        .await
+        .map_err(DashlensError::database("read setting"))?;
+    Ok(row.map(|(value,)| value))
+}
+
+pub async fn set<'e>(executor: impl SqliteExecutor<'e>, key: &str, value: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO app_settings (key, value) VALUES ($1, $2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+    )
+    .bind(key)
+    .bind(value)
+    .execute(executor)
+    .await
+    .map_err(DashlensError::database("write setting"))?;
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use sqlx::{Pool, Sqlite};
+
+use crate::auth::now_secs;
+use crate::error::{DashlensError, Result};
+
+// ---------------------------------------------------------------------------
+// Failed-login tracking backed by the `login_attempts` table (migration v4).
+//
+// Attempts are keyed by the submitted username, whether or not the account
+// exists, so throttling does not reveal which usernames are registered.
+// The first few failures are free; after that each failure doubles the wait
+// before the next attempt, and past LOCKOUT_AFTER failures the account is
+// locked for LOCKOUT_SECS. A successful login clears the counter.
+//
+// An attempt is counted before the password is checked (`claim_attempt`,
+// under SQLite's write lock), so concurrent logins cannot all slip past the
+// check and get a guess each. Once a lockout has run its course, or after
+// FAILURE_WINDOW_SECS without a failure, the count starts over.
+// ---------------------------------------------------------------------------
+
+const BACKOFF_AFTER: i64 = 3;
+const BACKOFF_BASE_SECS: i64 = 15;
+const LOCKOUT_AFTER: i64 = 5;
+const LOCKOUT_SECS: i64 = 15 * 60;
+const FAILURE_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+// Seconds the user must wait after `failed` consecutive failures
+fn retry_delay_secs(failed: i64) -> i64 {
+    if failed < BACKOFF_AFTER {
+        0
+    } else if failed >= LOCKOUT_AFTER {
+        LOCKOUT_SECS
+    } else {
+        BACKOFF_BASE_SECS << (failed - BACKOFF_AFTER)
+    }
+}
+
+// Failures that still count at `now`: none once a lockout has expired or
+// the last failure is older than the window
+fn live_failures(failed: i64, locked_until: Option<i64>, last_failed_at: i64, now: i64) -> i64 {
+    let lockout_served = failed >= LOCKOUT_AFTER && locked_until.is_some_and(|until| until <= now);
+    if lockout_served || now - last_failed_at >= FAILURE_WINDOW_SECS {
+        0
+    } else {
+        failed
+    }
+}
+
+pub enum Attempt {
+    // Still waiting out an earlier failure
+    Locked { retry_after_secs: i64 },
+    // Counted; the wait that applies if this attempt fails (0 if none)
+    Claimed { wait_if_failed: i64 },
+}
+
+// Count an attempt against `username` before its credentials are checked.
+// A successful attempt is then cleared with `clear_failures`.
+pub async fn claim_attempt(pool: &Pool<Sqlite>, username: &str) -> Result<Attempt> {
+    let mut tx = crate::db::begin_write(pool).await?;
+    let row: Option<(i64, Option<i64>, i64)> =
+        sqlx::query_as("SELECT failed_count, locked_until, last_failed_at FROM login_attempts WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(DashlensError::database("read login attempts"))?;
+
+    let now = now_secs();
+    let failed = match row {
+        Some((_, Some(locked_until), _)) if locked_until > now => {
+            return Ok(Attempt::Locked {
+                retry_after_secs: locked_until - now,
+            });
+        }
+        Some((failed, locked_until, last_failed_at)) => live_failures(failed, locked_until, last_failed_at, now) + 1,
+        None => 1,
+    };
+    let delay = retry_delay_secs(failed);
+    sqlx::query(
+        "INSERT INTO login_attempts (username, failed_count, last_failed_at, locked_until)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT(username) DO UPDATE SET
+           failed_count   = excluded.failed_count,
+           last_failed_at = excluded.last_failed_at,
+           locked_until   = excluded.locked_until",
+    )
+    .bind(username)
+    .bind(failed)
+    .bind(now)
+    .bind((delay > 0).then_some(now + delay))
+    .execute(&mut *tx)
+    .await
+    .map_err(DashlensError::database("record login attempt"))?;
+    tx.commit().await.map_err(DashlensError::database("record login attempt"))?;
+
+    Ok(Attempt::Claimed { wait_if_failed: delay })
+}
+
+// Give back a claimed attempt that was neither a success nor a failure: the
+// password was right but a TOTP code is still needed
+pub async fn refund_attempt(pool: &Pool<Sqlite>, username: &str) -> Result<()> {
+    let mut tx = crate::db::begin_write(pool).await?;
+    let row: Option<(i64, i64)> =
+        sqlx::query_as("SELECT failed_count, last_failed_at FROM login_attempts WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(DashlensError::database("read login attempts"))?;
+    let Some((failed, last_failed_at)) = row else {
+        return Ok(());
+    };
+    let failed = (failed - 1).max(0);
+    let delay = retry_delay_secs(failed);
+    sqlx::query("UPDATE login_attempts SET failed_count = $1, locked_until = $2 WHERE username = $3")
+        .bind(failed)
+        .bind((delay > 0).then_some(last_failed_at + delay))
+        .bind(username)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("record login attempt"))?;
+    tx.commit().await.map_err(DashlensError::database("record login attempt"))?;
+    Ok(())
+}
+
+pub async fn clear_failures(pool: &Pool<Sqlite>, username: &str) -> Result<()> {
+    sqlx::query("DELETE FROM login_attempts WHERE username = $1")
+        .bind(username)
+        .execute(pool)
+        .await
+        .map_err(DashlensError::database("reset login attempts"))?;
+    Ok(())
+}
+
+// Human-readable wait, e.g. "30 seconds" or "2 minutes"
+pub fn describe_wait(secs: i64) -> String {
+    if secs < 60 {
+        format!("{} second{}", secs, if secs == 1 { "" } else { "s" })
+    } else {
+        let minutes = (secs + 59) / 60;
+        format!("{} minute{}", minutes, if minutes == 1 { "" } else { "s" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_then_locks_out() {
+        assert_eq!(retry_delay_secs(1), 0);
+        assert_eq!(retry_delay_secs(2), 0);
+        assert_eq!(retry_delay_secs(3), 15);
+        assert_eq!(retry_delay_secs(4), 30);
+        assert_eq!(retry_delay_secs(5), LOCKOUT_SECS);
+        assert_eq!(retry_delay_secs(9), LOCKOUT_SECS);
+    }
+
+    #[test]
+    fn count_starts_over_after_a_served_lockout() {
+        let now = 1_000_000;
+        // Still inside the backoff stages: keep counting
+        assert_eq!(live_failures(4, Some(now - 1), now - 30, now), 4);
+        // Lockout served: the next typo is failure #1 again
+        assert_eq!(live_failures(5, Some(now - 1), now - LOCKOUT_SECS, now), 0);
+        assert_eq!(live_failures(5, Some(now + 60), now, now), 5);
+    }
+
+    #[test]
+    fn old_failures_are_forgotten() {
+        let now = 1_000_000;
+        assert_eq!(live_failures(2, None, now - FAILURE_WINDOW_SECS, now), 0);
+        assert_eq!(live_failures(2, None, now - FAILURE_WINDOW_SECS + 1, now), 2);
+    }
+}
@@ -0,0 +1,380 @@
+use hmac::{Hmac, Mac};
+use qrcode::{render::svg, QrCode};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sqlx::{Pool, Sqlite};
+use tauri::State;
+use tauri_plugin_sql::DbInstances;
+
+use crate::auth::now_secs;
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+use crate::secret::SecretString;
+
+// ---------------------------------------------------------------------------
+// Optional RFC 6238 TOTP second factor (HMAC-SHA1, 6 digits, 30s step —
+// the defaults every authenticator app supports).
+//
+// Secrets live in `user_totp` (migration v6). Enrollment writes a pending
+// secret that only becomes active once a code from it has been confirmed.
+// The last accepted time step is stored per user so a code cannot be
+// replayed, even within its validity window.
+//
+// Everything below takes `now` explicitly so it can be driven by a fixed
+// clock; the commands pass `now_secs()`.
+// ---------------------------------------------------------------------------
+
+const STEP_SECS: i64 = 30;
+const DIGITS: u32 = 6;
+const WINDOW: i64 = 1; // accept codes one step either side of now
+const SECRET_LEN: usize = 20;
+const ISSUER: &str = "Dashlens";
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[derive(Debug, Serialize)]
+pub struct TotpEnrollment {
+    pub secret: String, // base32, for manual entry
+    pub otpauth_uri: String,
+    pub qr_svg: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DisableTotpRequest {
+    pub password: SecretString,
+}
+
+// RFC 4648 base32 without padding, as used in otpauth:// URIs
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+pub fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in encoded.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+pub fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; SECRET_LEN];
+    OsRng.fill_bytes(&mut secret);
+    secret
+}
+
+// RFC 4226 HOTP value for a counter (the TOTP time step)
+pub fn code_at(secret: &[u8], step: i64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+pub fn step_at(now: i64) -> i64 {
+    now.div_euclid(STEP_SECS)
+}
+
+// Step the code matched within ±WINDOW of `now`, or None. Steps at or before
+// `last_used_step` are rejected so an accepted code cannot be reused.
+pub fn verify_code(secret: &[u8], code: &str, now: i64, last_used_step: Option<i64>) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let current = step_at(now);
+
+    (current - WINDOW..=current + WINDOW)
+        .filter(|&step| last_used_step.is_none_or(|last| step > last))
+        .find(|&step| code_at(secret, step) == code)
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub fn otpauth_uri(secret_b32: &str, username: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(ISSUER),
+        percent_encode(username),
+        secret_b32,
+        percent_encode(ISSUER),
+        DIGITS,
+        STEP_SECS
+    )
+}
+
+pub fn qr_svg(data: &str) -> Result<String> {
+    let code = QrCode::new(data.as_bytes())
+        .map_err(|e| DashlensError::Internal(format!("Failed to build QR code: {}", e)))?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .dark_color(svg::Color("#000000"))
+        .light_color(svg::Color("#ffffff"))
+        .build())
+}
+
+// ---------------------------------------------------------------------------
+// Storage
+// ---------------------------------------------------------------------------
+
+// (secret, last_used_step) for a user with TOTP switched on
+pub async fn enabled_secret(pool: &Pool<Sqlite>, user_id: i64) -> Result<Option<(Vec<u8>, Option<i64>)>> {
+    let row: Option<(String, Option<i64>)> = sqlx::query_as(
+        "SELECT secret, last_used_step FROM user_totp WHERE user_id = $1 AND enabled = 1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(DashlensError::database("read two-factor settings"))?;
+
+    row.map(|(secret, last)| {
+        base32_decode(&secret)
+            .map(|secret| (secret, last))
+            .ok_or_else(|| DashlensError::Internal("Stored two-factor secret is corrupt".into()))
+    })
+    .transpose()
+}
+
+// Record an accepted step. The `last_used_step < $1` guard makes this the
+// authoritative replay check when two logins race with the same code.
+pub async fn mark_used(pool: &Pool<Sqlite>, user_id: i64, step: i64) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE user_totp SET last_used_step = $1
+         WHERE user_id = $2 AND (last_used_step IS NULL OR last_used_step < $1)",
+    )
+    .bind(step)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .map_err(DashlensError::database("record two-factor code"))?;
+    Ok(result.rows_affected() == 1)
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+// Generate a new (pending) secret for the logged-in user. TOTP is not
+// required at login until `confirm_totp_enrollment` succeeds.
+#[tauri::command]
+pub async fn begin_totp_enrollment(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<TotpEnrollment> {
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let enabled: Option<(i64,)> = sqlx::query_as("SELECT 1 FROM user_totp WHERE user_id = $1 AND enabled = 1")
+        .bind(user_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(DashlensError::database("read two-factor settings"))?;
+    if enabled.is_some() {
+        return Err(DashlensError::Conflict("Two-factor authentication is already enabled".into()));
+    }
+
+    let secret = base32_encode(&generate_secret());
+    sqlx::query(
+        "INSERT INTO user_totp (user_id, secret, enabled, last_used_step, created_at)
+         VALUES ($1, $2, 0, NULL, $3)
+         ON CONFLICT(user_id) DO UPDATE SET
+           secret         = excluded.secret,
+           enabled        = 0,
+           last_used_step = NULL,
+           created_at     = excluded.created_at",
+    )
+    .bind(user_id)
+    .bind(&secret)
+    .bind(now_secs())
+    .execute(&pool)
+    .await
+    .map_err(DashlensError::database("store two-factor secret"))?;
+
+    let otpauth_uri = otpauth_uri(&secret, &user.username);
+    let qr_svg = qr_svg(&otpauth_uri)?;
+    Ok(TotpEnrollment {
+        secret,
+        otpauth_uri,
+        qr_svg,
+    })
+}
+
+// Activate the pending secret once the user proves their app produces codes for it
+#[tauri::command]
+pub async fn confirm_totp_enrollment(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    code: String,
+) -> Result<()> {
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let pending: Option<(String,)> =
+        sqlx::query_as("SELECT secret FROM user_totp WHERE user_id = $1 AND enabled = 0")
+            .bind(user_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(DashlensError::database("read two-factor settings"))?;
+    let (secret,) = pending.ok_or_else(|| DashlensError::NotFound("No two-factor enrollment in progress".into()))?;
+    let secret = base32_decode(&secret)
+        .ok_or_else(|| DashlensError::Internal("Stored two-factor secret is corrupt".into()))?;
+
+    let step = verify_code(&secret, &code, now_secs(), None)
+        .ok_or_else(|| DashlensError::InvalidCredentials("Invalid authentication code".into()))?;
+
+    sqlx::query("UPDATE user_totp SET enabled = 1, last_used_step = $1 WHERE user_id = $2")
+        .bind(step)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(DashlensError::database("enable two-factor authentication"))?;
+    Ok(())
+}
+
+// Turn TOTP off; requires the account password
+#[tauri::command]
+pub async fn disable_totp(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+    request: DisableTotpRequest,
+) -> Result<()> {
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let mut conn = pool.acquire().await.map_err(DashlensError::database("open connection"))?;
+    crate::vault::require_password(&mut conn, user_id, &request.password).await?;
+
+    sqlx::query("DELETE FROM user_totp WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(DashlensError::database("disable two-factor authentication"))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn totp_status(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<bool> {
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+    Ok(enabled_secret(&pool, user_id).await?.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 appendix B, SHA-1 key; the expected codes are the low six
+    // digits of the published eight-digit values
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn matches_rfc_6238_vectors() {
+        let vectors = [
+            (59, 287_082),
+            (1_111_111_109, 81_804),
+            (1_111_111_111, 50_471),
+            (1_234_567_890, 5_924),
+            (2_000_000_000, 279_037),
+            (20_000_000_000, 353_130),
+        ];
+        for (time, expected) in vectors {
+            assert_eq!(code_at(RFC_SECRET, step_at(time)), expected, "T = {}", time);
+        }
+    }
+
+    #[test]
+    fn accepts_one_step_either_side() {
+        let now = 1_111_111_111;
+        let current = step_at(now);
+        for step in [current - 1, current, current + 1] {
+            let code = format!("{:06}", code_at(RFC_SECRET, step));
+            assert_eq!(verify_code(RFC_SECRET, &code, now, None), Some(step));
+        }
+        for step in [current - 2, current + 2] {
+            let code = format!("{:06}", code_at(RFC_SECRET, step));
+            assert_eq!(verify_code(RFC_SECRET, &code, now, None), None);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        let now = 59;
+        assert_eq!(verify_code(RFC_SECRET, " 287082 ", now, None), Some(1));
+        assert_eq!(verify_code(RFC_SECRET, "28708", now, None), None);
+        assert_eq!(verify_code(RFC_SECRET, "2870821", now, None), None);
+        assert_eq!(verify_code(RFC_SECRET, "28708a", now, None), None);
+        assert_eq!(verify_code(RFC_SECRET, "+28708", now, None), None);
+    }
+
+    #[test]
+    fn rejects_replayed_steps() {
+        let now = 1_234_567_890;
+        let current = step_at(now);
+        let code = format!("{:06}", code_at(RFC_SECRET, current));
+        assert_eq!(verify_code(RFC_SECRET, &code, now, Some(current - 1)), Some(current));
+        assert_eq!(verify_code(RFC_SECRET, &code, now, Some(current)), None);
+        // An older code is no good once a later one has been used
+        let previous = format!("{:06}", code_at(RFC_SECRET, current - 1));
+        assert_eq!(verify_code(RFC_SECRET, &previous, now, Some(current)), None);
+    }
+
+    #[test]
+    fn base32_round_trips() {
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("mzxw6ytboi======").as_deref(), Some(&b"foobar"[..]));
+        assert_eq!(base32_decode("MZXW 6YTB OI").as_deref(), Some(&b"foobar"[..]));
+        assert_eq!(base32_decode("MZXW1"), None);
+        for len in 0..=SECRET_LEN {
+            let bytes: Vec<u8> = (0..len as u8).map(|b| b.wrapping_mul(37)).collect();
+            assert_eq!(base32_decode(&base32_encode(&bytes)), Some(bytes));
+        }
+        let secret = generate_secret();
+        assert_eq!(base32_decode(&base32_encode(&secret)), Some(secret));
+    }
+}
@@ -0,0 +1,358 @@
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+use tauri_plugin_sql::DbInstances;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::auth::now_secs;
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+
+// ---------------------------------------------------------------------------
+// Canonical usernames.
+//
+// Usernames are stored and compared in canonical form: NFKC-normalized,
+// trimmed and lowercased, so "Alex", "alex " and the full-width "ａｌｅｘ"
+// are the same account. Register rejects names that break the rules below;
+// login and recovery only normalize, so a malformed name simply fails as
+// unknown. A COLLATE NOCASE unique index backs this up in the database; it
+// is created by `canonicalize_existing` once older names are canonical.
+// ---------------------------------------------------------------------------
+
+const MIN_LEN: usize = 3;
+const MAX_LEN: usize = 32;
+
+// Separators allowed between letters and digits
+const SEPARATORS: &[char] = &['.', '_', '-'];
+
+pub fn normalize(raw: &str) -> String {
+    let composed: String = raw.nfkc().collect();
+    composed.trim().to_lowercase().nfkc().collect()
+}
+
+// Canonical form of a new username, or why it is not allowed
+pub fn validate(raw: &str) -> Result<String> {
+    let username = normalize(raw);
+    let len = username.chars().count();
+    if !(MIN_LEN..=MAX_LEN).contains(&len) {
+        return Err(DashlensError::Validation(format!(
+            "Username must be between {} and {} characters",
+            MIN_LEN, MAX_LEN
+        )));
+    }
+    if !username.chars().all(|c| c.is_alphanumeric() || SEPARATORS.contains(&c)) {
+        return Err(DashlensError::Validation(
+            "Username may only contain letters, digits, '.', '_' and '-'".into(),
+        ));
+    }
+    let starts_and_ends_alphanumeric = username.chars().next().is_some_and(char::is_alphanumeric)
+        && username.chars().last().is_some_and(char::is_alphanumeric);
+    if !starts_and_ends_alphanumeric {
+        return Err(DashlensError::Validation(
+            "Username must start and end with a letter or digit".into(),
+        ));
+    }
+    Ok(username)
+}
+
+// Bring names stored before migration v10 into canonical form, then add the
+// unique index. Where several accounts share a canonical name the oldest
+// keeps it and the others become "<name>-<id>" (plus "-2", "-3", ... should
+// that be taken as well), each recorded in username_conflicts. Runs at every
+// startup and does nothing once all names are canonical.
+pub async fn canonicalize_existing(pool: &Pool<Sqlite>) -> Result<()> {
+    let mut tx = crate::db::begin_write(pool).await?;
+    let users: Vec<(i64, String)> = sqlx::query_as("SELECT id, username FROM users ORDER BY id")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DashlensError::database("read users"))?;
+
+    let renames = plan_renames(&users);
+    if !renames.is_empty() {
+        // Renames may pass through names the index would reject mid-way
+        sqlx::query("DROP INDEX IF EXISTS idx_users_username_nocase")
+            .execute(&mut *tx)
+            .await
+            .map_err(DashlensError::database("normalize usernames"))?;
+    }
+    let detected_at = now_secs();
+    for rename in &renames {
+        sqlx::query("UPDATE users SET username = $1 WHERE id = $2")
+            .bind(&rename.renamed_to)
+            .bind(rename.user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(DashlensError::database("normalize usernames"))?;
+        let Some(conflicts_with) = rename.conflicts_with else {
+            continue;
+        };
+        sqlx::query(
+            "INSERT INTO username_conflicts
+                 (user_id, original_username, renamed_to, conflicts_with, detected_at)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(rename.user_id)
+        .bind(&rename.original_username)
+        .bind(&rename.renamed_to)
+        .bind(conflicts_with)
+        .bind(detected_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("record username conflict"))?;
+    }
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_users_username_nocase ON users(username COLLATE NOCASE)")
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("index usernames"))?;
+    tx.commit().await.map_err(DashlensError::database("normalize usernames"))?;
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+struct Rename {
+    user_id: i64,
+    original_username: String,
+    renamed_to: String,
+    conflicts_with: Option<i64>, // set when the canonical name went to an older account
+}
+
+// Renames for `canonicalize_existing`; `users` is in id order
+fn plan_renames(users: &[(i64, String)]) -> Vec<Rename> {
+    let mut keeper: HashMap<String, i64> = HashMap::new();
+    for (id, username) in users {
+        keeper.entry(normalize(username)).or_insert(*id);
+    }
+    let mut taken: HashSet<String> = keeper.keys().cloned().collect();
+
+    let mut renames = Vec::new();
+    for (id, username) in users {
+        let canonical = normalize(username);
+        let kept_by = keeper[&canonical];
+        let (renamed_to, conflicts_with) = if kept_by == *id {
+            (canonical, None)
+        } else {
+            let base = format!("{}-{}", canonical, id);
+            let mut candidate = base.clone();
+            let mut n = 2;
+            while !taken.insert(candidate.clone()) {
+                candidate = format!("{}-{}", base, n);
+                n += 1;
+            }
+            (candidate, Some(kept_by))
+        };
+        if renamed_to != *username {
+            renames.push(Rename {
+                user_id: *id,
+                original_username: username.clone(),
+                renamed_to,
+                conflicts_with,
+            });
+        }
+    }
+    renames
+}
+
+// Accounts a login form's username may refer to, as (id, username,
+// password_hash): the one holding the canonical name, then any account
+// renamed by `canonicalize_existing` whose exact old spelling this is and
+// which has not logged in since. The renamed user has no way to learn
+// their new name before logging in, so their old one keeps working until
+// then; `mark_resolved` retires it.
+pub async fn login_candidates(pool: &Pool<Sqlite>, raw: &str) -> Result<Vec<(i64, String, String)>> {
+    let mut candidates: Vec<(i64, String, String)> =
+        sqlx::query_as("SELECT id, username, password_hash FROM users WHERE username = $1 COLLATE NOCASE")
+            .bind(normalize(raw))
+            .fetch_all(pool)
+            .await
+            .map_err(DashlensError::database("look up user"))?;
+    let renamed: Vec<(i64, String, String)> = sqlx::query_as(
+        "SELECT users.id, users.username, users.password_hash
+           FROM username_conflicts
+           JOIN users ON users.id = username_conflicts.user_id
+          WHERE TRIM(username_conflicts.original_username) = TRIM($1)
+            AND username_conflicts.resolved_at IS NULL
+          ORDER BY username_conflicts.id",
+    )
+    .bind(raw)
+    .fetch_all(pool)
+    .await
+    .map_err(DashlensError::database("look up user"))?;
+    for account in renamed {
+        if !candidates.iter().any(|(id, _, _)| *id == account.0) {
+            candidates.push(account);
+        }
+    }
+    Ok(candidates)
+}
+
+// The account has logged in under its new name; its old spelling now
+// means only what it normalizes to
+pub async fn mark_resolved(pool: &Pool<Sqlite>, user_id: i64) -> Result<()> {
+    sqlx::query("UPDATE username_conflicts SET resolved_at = $1 WHERE user_id = $2 AND resolved_at IS NULL")
+        .bind(now_secs())
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(DashlensError::database("record username conflict"))?;
+    Ok(())
+}
+
+// An account renamed by `canonicalize_existing` because its name collided
+// with an older account's once normalized
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct UsernameConflict {
+    pub user_id: i64,
+    pub original_username: String,
+    pub renamed_to: String,
+    pub conflicts_with: i64, // id of the account that kept the name
+    pub detected_at: i64,    // unix seconds
+    pub resolved_at: Option<i64>, // first login after the rename
+}
+
+// Renames affecting the logged-in user: their own account, and for an owner
+// also their viewers'
+#[tauri::command]
+pub async fn list_username_conflicts(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<Vec<UsernameConflict>> {
+    let pool = crate::db::pool(&db).await?;
+    sqlx::query_as(
+        "SELECT user_id, original_username, renamed_to, conflicts_with, detected_at, resolved_at
+           FROM username_conflicts
+          WHERE user_id = $1
+             OR user_id IN (SELECT id FROM users WHERE owner_id = $1)
+          ORDER BY id",
+    )
+    .bind(user.user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(DashlensError::database("read username conflicts"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users(names: &[&str]) -> Vec<(i64, String)> {
+        names.iter().enumerate().map(|(i, name)| (i as i64 + 1, name.to_string())).collect()
+    }
+
+    fn renamed(renames: &[Rename]) -> Vec<(i64, &str, Option<i64>)> {
+        renames
+            .iter()
+            .map(|r| (r.user_id, r.renamed_to.as_str(), r.conflicts_with))
+            .collect()
+    }
+
+    #[test]
+    fn normalizes_width_case_and_spaces() {
+        assert_eq!(normalize("  Alex "), "alex");
+        assert_eq!(normalize("ＡＬＥＸ"), "alex");
+        assert_eq!(normalize("ÉMILE"), "émile");
+        assert_eq!(normalize("E\u{301}mile"), "émile");
+    }
+
+    #[test]
+    fn validates_new_names() {
+        assert_eq!(validate(" Driver_1 ").unwrap(), "driver_1");
+        assert!(validate("ab").is_err());
+        assert!(validate(&"a".repeat(MAX_LEN + 1)).is_err());
+        assert!(validate("two words").is_err());
+        assert!(validate("-dash").is_err());
+        assert!(validate("dash.").is_err());
+    }
+
+    #[test]
+    fn canonical_names_need_no_renames() {
+        assert!(plan_renames(&users(&["alex", "émile", "sam-2"])).is_empty());
+    }
+
+    #[test]
+    fn oldest_account_keeps_a_unicode_duplicate() {
+        // Full-width and accented capitals only fold outside ASCII
+        let renames = plan_renames(&users(&["ａｌｅｘ", "Alex", "ÉMILE", "émile"]));
+        assert_eq!(
+            renamed(&renames),
+            vec![(1, "alex", None), (2, "alex-2", Some(1)), (3, "émile", None), (4, "émile-4", Some(3))]
+        );
+        assert_eq!(renames[1].original_username, "Alex");
+    }
+
+    #[test]
+    fn existing_accounts_are_canonicalized_once() {
+        tauri::async_runtime::block_on(async {
+            let pool = crate::db::memory_pool().await;
+            sqlx::raw_sql(
+                "INSERT INTO users (id, username, password_hash) VALUES
+                     (1, 'ＡＬＥＸ', 'x'), (2, ' alex', 'x'), (3, 'alex-2', 'x'), (4, 'Sam', 'x');",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+            canonicalize_existing(&pool).await.unwrap();
+            let names: Vec<String> = sqlx::query_scalar("SELECT username FROM users ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            assert_eq!(names, ["alex", "alex-2-2", "alex-2", "sam"]);
+            let conflicts: Vec<(i64, String, i64)> =
+                sqlx::query_as("SELECT user_id, renamed_to, conflicts_with FROM username_conflicts")
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(conflicts, [(2, "alex-2-2".to_string(), 1)]);
+
+            // The index now rejects another spelling, and a second run is a no-op
+            let duplicate = sqlx::query("INSERT INTO users (username, password_hash) VALUES ('SAM', 'x')")
+                .execute(&pool)
+                .await;
+            assert!(duplicate.is_err());
+            canonicalize_existing(&pool).await.unwrap();
+            let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM username_conflicts")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(count, 1);
+        });
+    }
+
+    #[test]
+    fn renamed_names_never_collide() {
+        // "alex-2" is already someone's name, and so is "alex-2-2"
+        let renames = plan_renames(&users(&["alex", "ALEX", "Alex-2", "alex-2-2"]));
+        assert_eq!(renamed(&renames), vec![(2, "alex-2-3", Some(1)), (3, "alex-2", None)]);
+    }
+
+    #[test]
+    fn old_spelling_reaches_the_renamed_account_until_it_logs_in() {
+        tauri::async_runtime::block_on(async {
+            let pool = crate::db::memory_pool().await;
+            sqlx::raw_sql(
+                "INSERT INTO users (id, username, password_hash) VALUES
+                     (1, 'alex', 'kept'), (2, 'Alex', 'renamed'), (3, 'sam', 'other');",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            canonicalize_existing(&pool).await.unwrap();
+
+            let ids = |candidates: Vec<(i64, String, String)>| -> Vec<i64> {
+                candidates.into_iter().map(|(id, _, _)| id).collect()
+            };
+            // The canonical owner of the name is tried first
+            assert_eq!(ids(login_candidates(&pool, "Alex").await.unwrap()), [1, 2]);
+            assert_eq!(ids(login_candidates(&pool, " Alex ").await.unwrap()), [1, 2]);
+            assert_eq!(ids(login_candidates(&pool, "alex").await.unwrap()), [1]);
+            assert_eq!(ids(login_candidates(&pool, "ALEX").await.unwrap()), [1]);
+            assert_eq!(ids(login_candidates(&pool, "alex-2").await.unwrap()), [2]);
+            assert!(login_candidates(&pool, "nobody").await.unwrap().is_empty());
+
+            mark_resolved(&pool, 2).await.unwrap();
+            assert_eq!(ids(login_candidates(&pool, "Alex").await.unwrap()), [1]);
+            assert_eq!(ids(login_candidates(&pool, "alex-2").await.unwrap()), [2]);
+        });
+    }
+}
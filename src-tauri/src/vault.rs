@@ -0,0 +1,484 @@
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use std::fmt;
+use tauri::{AppHandle, State};
+use tauri_plugin_sql::DbInstances;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::auth::{now_secs, AuthState};
+use crate::error::{DashlensError, Result};
+use crate::guard::CurrentUser;
+use crate::money::Money;
+use crate::password::{self, Argon2Policy};
+use crate::secret::SecretString;
+
+// ---------------------------------------------------------------------------
+// Optional at-rest encryption of earnings (table `user_keys`, migration v9).
+//
+// The earnings columns of sessions and offers are encrypted field by field
+// with XChaCha20-Poly1305 under a random per-user data key. That key is never
+// stored in the clear: `user_keys` holds it wrapped under a key derived with
+// Argon2 from the login password, and each recovery code wraps it too so a
+// recovered account keeps its data. A password change only re-wraps the data
+// key; rows are re-encrypted just when encryption is switched on or off.
+//
+// The unwrapped key lives in AuthState for the length of the session and is
+// handed to commands through CurrentUser. Remember-me is unavailable for
+// encrypted accounts, since a restored session has no password to unlock with.
+//
+// SQLCipher is not used because the SQL plugin opens and migrates the
+// database at startup, before any password is known.
+// ---------------------------------------------------------------------------
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+// Prefix of an encrypted field value; plaintext amounts are plain integer
+// cents. Values sealed before v13 hold dollars under the v1 prefix.
+const SEALED_PREFIX: &str = "enc2:";
+const LEGACY_SEALED_PREFIX: &str = "enc1:";
+
+// The random per-user key earnings are encrypted with; wiped on drop
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct DataKey([u8; KEY_LEN]);
+
+impl fmt::Debug for DataKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DataKey(<redacted>)")
+    }
+}
+
+impl DataKey {
+    pub(crate) fn generate() -> Self {
+        let mut key = [0u8; KEY_LEN];
+        OsRng.fill_bytes(&mut key);
+        DataKey(key)
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.0))
+    }
+
+    fn seal(&self, plaintext: &[u8]) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher()
+            .encrypt(XNonce::from_slice(&nonce), plaintext)
+            .map_err(|_| DashlensError::Internal("Failed to encrypt data".into()))?;
+        Ok(hex::encode([nonce.as_slice(), &ciphertext].concat()))
+    }
+
+    // None when the ciphertext does not authenticate under this key
+    fn open(&self, sealed: &str) -> Option<Zeroizing<Vec<u8>>> {
+        let bytes = hex::decode(sealed).ok()?;
+        if bytes.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        self.cipher()
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .ok()
+            .map(Zeroizing::new)
+    }
+}
+
+// A data key wrapped under an Argon2-derived key; stored as JSON
+#[derive(Serialize, Deserialize)]
+struct WrappedKey {
+    salt: String,       // hex
+    kdf: Argon2Policy,  // parameters the wrapping key was derived with
+    key: String,        // hex nonce || ciphertext
+}
+
+fn derive_key(secret: &str, salt: &[u8], policy: &Argon2Policy) -> Result<DataKey> {
+    let mut key = DataKey([0u8; KEY_LEN]);
+    policy
+        .hasher()?
+        .hash_password_into(secret.as_bytes(), salt, &mut key.0)
+        .map_err(|e| DashlensError::Internal(format!("Failed to derive key: {}", e)))?;
+    Ok(key)
+}
+
+// Wrap `key` under `secret` (a password or recovery code)
+pub fn wrap_key(key: &DataKey, secret: &str, policy: &Argon2Policy) -> Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let wrapping_key = derive_key(secret, &salt, policy)?;
+    let wrapped = WrappedKey {
+        salt: hex::encode(salt),
+        kdf: *policy,
+        key: wrapping_key.seal(&key.0)?,
+    };
+    serde_json::to_string(&wrapped)
+        .map_err(|e| DashlensError::Internal(format!("Failed to encode wrapped key: {}", e)))
+}
+
+// Recover the data key; None if `secret` is not the one it was wrapped under
+pub fn unwrap_key(wrapped: &str, secret: &str) -> Result<Option<DataKey>> {
+    let wrapped: WrappedKey = serde_json::from_str(wrapped)
+        .map_err(|_| DashlensError::Internal("Stored encryption key is corrupt".into()))?;
+    let salt = hex::decode(&wrapped.salt)
+        .map_err(|_| DashlensError::Internal("Stored encryption key is corrupt".into()))?;
+    let wrapping_key = derive_key(secret, &salt, &wrapped.kdf)?;
+
+    Ok(wrapping_key.open(&wrapped.key).and_then(|bytes| {
+        let key: [u8; KEY_LEN] = bytes.as_slice().try_into().ok()?;
+        Some(DataKey(key))
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Field encoding
+// ---------------------------------------------------------------------------
+
+// Value to store for an amount: the cents themselves, or sealed under `key`.
+// Bound as TEXT either way; SQLite's INTEGER affinity turns plain numbers
+// back into INTEGER, while sealed values stay TEXT.
+pub fn seal_amount(key: Option<&DataKey>, amount: Option<Money>) -> Result<Option<String>> {
+    let Some(amount) = amount else {
+        return Ok(None);
+    };
+    let cents = amount.cents().to_string();
+    match key {
+        Some(key) => Ok(Some(format!("{}{}", SEALED_PREFIX, key.seal(cents.as_bytes())?))),
+        None => Ok(Some(cents)),
+    }
+}
+
+// Inverse of seal_amount; read the column as `CAST(col AS TEXT)`
+pub fn open_amount(key: Option<&DataKey>, stored: Option<String>) -> Result<Option<Money>> {
+    let Some(stored) = stored else {
+        return Ok(None);
+    };
+    let (sealed, legacy) = match (stored.strip_prefix(SEALED_PREFIX), stored.strip_prefix(LEGACY_SEALED_PREFIX)) {
+        (Some(sealed), _) => (sealed, false),
+        (None, Some(sealed)) => (sealed, true),
+        (None, None) => {
+            return stored
+                .parse()
+                .map(|cents| Some(Money::from_cents(cents)))
+                .map_err(|_| DashlensError::Internal(format!("Stored amount is not in cents: {}", stored)));
+        }
+    };
+    let key = key.ok_or_else(|| DashlensError::Unauthenticated("Encrypted data is locked; log in again".into()))?;
+    let bytes = key
+        .open(sealed)
+        .ok_or_else(|| DashlensError::Internal("Encrypted amount could not be decrypted".into()))?;
+    let text = std::str::from_utf8(&bytes).ok();
+    let amount = if legacy {
+        text.and_then(|text| text.parse().ok()).and_then(Money::from_dollars)
+    } else {
+        text.and_then(|text| text.parse().ok()).map(Money::from_cents)
+    };
+    amount
+        .map(Some)
+        .ok_or_else(|| DashlensError::Internal("Encrypted amount is corrupt".into()))
+}
+
+// A session's id and its earnings fields as stored (plain or sealed)
+type StoredSessionAmounts = (i64, Option<String>, Option<String>, Option<String>);
+
+// Rewrite every earnings field the user owns from `from` encoding to `to`
+async fn reseal_user_rows(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    from: Option<&DataKey>,
+    to: Option<&DataKey>,
+) -> Result<()> {
+    let sessions: Vec<StoredSessionAmounts> = sqlx::query_as(
+        "SELECT id, CAST(total_earnings AS TEXT), CAST(base_pay AS TEXT), CAST(tips AS TEXT)
+           FROM sessions WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(DashlensError::database("read sessions"))?;
+
+    for (id, total_earnings, base_pay, tips) in sessions {
+        sqlx::query("UPDATE sessions SET total_earnings = $1, base_pay = $2, tips = $3 WHERE id = $4")
+            .bind(seal_amount(to, open_amount(from, total_earnings)?)?)
+            .bind(seal_amount(to, open_amount(from, base_pay)?)?)
+            .bind(seal_amount(to, open_amount(from, tips)?)?)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(DashlensError::database("re-encrypt session"))?;
+    }
+
+    let offers: Vec<(i64, Option<String>)> = sqlx::query_as(
+        "SELECT id, CAST(total_earnings AS TEXT) FROM offers
+          WHERE session_id IN (SELECT id FROM sessions WHERE user_id = $1)",
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(DashlensError::database("read offers"))?;
+
+    for (id, total_earnings) in offers {
+        sqlx::query("UPDATE offers SET total_earnings = $1 WHERE id = $2")
+            .bind(seal_amount(to, open_amount(from, total_earnings)?)?)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(DashlensError::database("re-encrypt offer"))?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Key storage
+// ---------------------------------------------------------------------------
+
+async fn wrapped_key(conn: &mut SqliteConnection, user_id: i64) -> Result<Option<String>> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT wrapped_key FROM user_keys WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(DashlensError::database("read encryption key"))?;
+    Ok(row.map(|(wrapped,)| wrapped))
+}
+
+pub async fn is_enabled(conn: &mut SqliteConnection, user_id: i64) -> Result<bool> {
+    Ok(wrapped_key(conn, user_id).await?.is_some())
+}
+
+// Unwrap the user's data key with their (already verified) password.
+// None when the user has not turned encryption on.
+pub async fn unlock(conn: &mut SqliteConnection, user_id: i64, password: &str) -> Result<Option<DataKey>> {
+    let Some(wrapped) = wrapped_key(conn, user_id).await? else {
+        return Ok(None);
+    };
+    unwrap_key(&wrapped, password)?
+        .map(Some)
+        .ok_or_else(|| DashlensError::Internal("Encryption key does not match the account password".into()))
+}
+
+// Store `key` wrapped under `password` (insert or replace)
+pub async fn store_key(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    key: &DataKey,
+    password: &str,
+    policy: &Argon2Policy,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO user_keys (user_id, wrapped_key, created_at) VALUES ($1, $2, $3)
+         ON CONFLICT(user_id) DO UPDATE SET wrapped_key = excluded.wrapped_key",
+    )
+    .bind(user_id)
+    .bind(wrap_key(key, password, policy)?)
+    .bind(now_secs())
+    .execute(&mut *conn)
+    .await
+    .map_err(DashlensError::database("store encryption key"))?;
+    Ok(())
+}
+
+// Re-key on password change: the data key is re-wrapped under the new
+// password. No-op for users without encryption.
+pub async fn rewrap(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    current_password: &str,
+    new_password: &str,
+    policy: &Argon2Policy,
+) -> Result<()> {
+    if let Some(key) = unlock(conn, user_id, current_password).await? {
+        store_key(conn, user_id, &key, new_password, policy).await?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct EncryptionRequest {
+    pub password: SecretString,
+}
+
+// Fail with InvalidCredentials unless `password` is the user's login password
+pub(crate) async fn require_password(conn: &mut SqliteConnection, user_id: i64, password: &str) -> Result<()> {
+    let (password_hash,): (String,) = sqlx::query_as("SELECT password_hash FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(DashlensError::database("look up user"))?;
+    if !password::verify(password, &password_hash)? {
+        return Err(DashlensError::InvalidCredentials("Password is incorrect".into()));
+    }
+    Ok(())
+}
+
+// Encrypt the logged-in user's earnings. Recovery codes are replaced so each
+// one can also unwrap the key; the new set is returned and must be shown to
+// the user. Remember-me tokens and any quick-unlock PIN are revoked.
+#[tauri::command]
+pub async fn enable_encryption(
+    app: AppHandle,
+    user: CurrentUser,
+    state: State<'_, AuthState>,
+    db: State<'_, DbInstances>,
+    request: EncryptionRequest,
+) -> Result<Vec<String>> {
+    user.require_owner()?;
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+    // Viewers read the owner's rows without a key of their own
+    if crate::roles::has_viewers(&pool, user_id).await? {
+        return Err(DashlensError::Conflict(
+            "Remove this account's viewers before enabling encryption".into(),
+        ));
+    }
+    let policy = password::current_policy(&pool).await?;
+
+    let mut tx = crate::db::begin_write(&pool).await?;
+    require_password(&mut tx, user_id, &request.password).await?;
+    if is_enabled(&mut tx, user_id).await? {
+        return Err(DashlensError::Conflict("Encryption is already enabled".into()));
+    }
+
+    let key = DataKey::generate();
+    store_key(&mut tx, user_id, &key, &request.password, &policy).await?;
+    reseal_user_rows(&mut tx, user_id, None, Some(&key)).await?;
+    let recovery_codes = crate::recovery::replace_codes(&mut tx, user_id, Some(&key)).await?;
+    sqlx::query("DELETE FROM auth_tokens WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("revoke remember-me tokens"))?;
+    // A PIN only unlocks a remembered account, so it has no use any more
+    sqlx::query("UPDATE users SET pin_hash = NULL, pin_failed_count = 0 WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("remove PIN"))?;
+    tx.commit().await.map_err(DashlensError::database("enable encryption"))?;
+
+    crate::remember::revoke(&app, &pool).await?;
+    state.set_data_key(Some(key))?;
+    Ok(recovery_codes)
+}
+
+// Decrypt the logged-in user's earnings back to plain columns
+#[tauri::command]
+pub async fn disable_encryption(
+    user: CurrentUser,
+    state: State<'_, AuthState>,
+    db: State<'_, DbInstances>,
+    request: EncryptionRequest,
+) -> Result<()> {
+    let user_id = user.user_id;
+    let pool = crate::db::pool(&db).await?;
+
+    let mut tx = crate::db::begin_write(&pool).await?;
+    require_password(&mut tx, user_id, &request.password).await?;
+    let key = unlock(&mut tx, user_id, &request.password)
+        .await?
+        .ok_or_else(|| DashlensError::Conflict("Encryption is not enabled".into()))?;
+
+    reseal_user_rows(&mut tx, user_id, Some(&key), None).await?;
+    sqlx::query("DELETE FROM user_keys WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("remove encryption key"))?;
+    sqlx::query("UPDATE recovery_codes SET wrapped_key = NULL WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DashlensError::database("update recovery codes"))?;
+    tx.commit().await.map_err(DashlensError::database("disable encryption"))?;
+
+    state.set_data_key(None)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn encryption_status(
+    user: CurrentUser,
+    db: State<'_, DbInstances>,
+) -> Result<bool> {
+    let pool = crate::db::pool(&db).await?;
+    let mut conn = pool.acquire().await.map_err(DashlensError::database("open connection"))?;
+    is_enabled(&mut conn, user.user_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_key_debug_is_redacted() {
+        let key = DataKey([0xab; KEY_LEN]);
+        assert_eq!(format!("{:?}", key), "DataKey(<redacted>)");
+        // Nor when it sits inside something that derives Debug
+        let debug = format!("{:?}", Some(key));
+        assert!(!debug.contains("171") && debug.contains("<redacted>"), "{}", debug);
+    }
+
+    fn cents(value: i64) -> Option<Money> {
+        Some(Money::from_cents(value))
+    }
+
+    #[test]
+    fn plain_amounts_are_stored_as_cents() {
+        assert_eq!(seal_amount(None, cents(1_234)).unwrap().as_deref(), Some("1234"));
+        assert_eq!(seal_amount(None, None).unwrap(), None);
+        assert_eq!(open_amount(None, Some("1234".into())).unwrap(), cents(1_234));
+        assert_eq!(open_amount(None, None).unwrap(), None);
+        // Dollars left over from before v13 are an error, not a guess
+        assert!(open_amount(None, Some("12.34".into())).is_err());
+    }
+
+    #[test]
+    fn sealed_amounts_need_their_key() {
+        let key = DataKey::generate();
+        let sealed = seal_amount(Some(&key), cents(1_234)).unwrap().unwrap();
+        assert!(sealed.starts_with(SEALED_PREFIX));
+        assert_eq!(open_amount(Some(&key), Some(sealed.clone())).unwrap(), cents(1_234));
+
+        let other = DataKey::generate();
+        assert!(open_amount(Some(&other), Some(sealed.clone())).is_err());
+        let locked = open_amount(None, Some(sealed)).unwrap_err();
+        assert_eq!(locked.code(), "unauthenticated");
+    }
+
+    // Values sealed before v13 hold the dollar amount as text
+    #[test]
+    fn legacy_sealed_dollars_become_cents() {
+        let key = DataKey::generate();
+        let legacy = |dollars: &str| Some(format!("{}{}", LEGACY_SEALED_PREFIX, key.seal(dollars.as_bytes()).unwrap()));
+        assert_eq!(open_amount(Some(&key), legacy("12.34")).unwrap(), cents(1_234));
+        assert_eq!(open_amount(Some(&key), legacy("0.1")).unwrap(), cents(10));
+        assert_eq!(open_amount(Some(&key), legacy("19.999")).unwrap(), cents(2_000));
+        assert_eq!(open_amount(Some(&key), legacy("7")).unwrap(), cents(700));
+        assert!(open_amount(Some(&key), legacy("seven")).is_err());
+        assert!(open_amount(Some(&key), legacy("NaN")).is_err());
+    }
+
+    // What recovery relies on: a code unwraps the key it wrapped, typed
+    // the way normalize_code leaves it, and nothing else does
+    #[test]
+    fn wrapped_key_round_trips() {
+        let policy = Argon2Policy {
+            variant: crate::password::Argon2Variant::Argon2id,
+            m_cost: 64,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        let key = DataKey::generate();
+        let wrapped = wrap_key(&key, "K7QX2MP9RT", &policy).unwrap();
+        let unwrapped = unwrap_key(&wrapped, "K7QX2MP9RT").unwrap().unwrap();
+        assert_eq!(unwrapped.0, key.0);
+
+        assert!(unwrap_key(&wrapped, "K7QX2MP9RA").unwrap().is_none());
+        assert!(unwrap_key("not json", "K7QX2MP9RT").is_err());
+    }
+}
@@ -0,0 +1,51 @@
+// The webview must not reach the database except through the named commands
+// (entries.rs, auth.rs, ...). Any `sql:` permission in a shipped capability
+// would let injected script run arbitrary statements such as
+// `DROP TABLE users`, so none is allowed.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+// Permission identifiers of a capability; entries are either a string or
+// an object with an `identifier`
+fn permissions(capability: &Value) -> Vec<String> {
+    capability["permissions"]
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| entry.as_str().or_else(|| entry["identifier"].as_str()))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn capabilities_grant_no_sql_access() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("capabilities");
+    let mut checked = 0;
+    for entry in fs::read_dir(&dir).expect("read capabilities directory") {
+        let path = entry.expect("read capabilities entry").path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let text = fs::read_to_string(&path).expect("read capability file");
+        let capability: Value = serde_json::from_str(&text).expect("parse capability file");
+
+        let sql: Vec<String> = permissions(&capability)
+            .into_iter()
+            .filter(|permission| permission == "sql" || permission.starts_with("sql:"))
+            .collect();
+        assert!(sql.is_empty(), "{} grants SQL plugin permissions: {:?}", path.display(), sql);
+        assert!(
+            !text.contains("allow-execute"),
+            "{} mentions a raw-execute permission",
+            path.display()
+        );
+        checked += 1;
+    }
+    assert!(checked > 0, "no capability files found in {}", dir.display());
+}
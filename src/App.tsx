@@ -1,5 +1,6 @@
 import { AuthProvider } from "@/contexts/AuthContext";
 import { ProtectedRoute } from "@/components/auth/ProtectedRoute";
+import { RecoveryCodesDialog } from "@/components/auth/RecoveryCodesDialog";
 import { AppSidebar } from "@/components/app-sidebar";
 import {
   Breadcrumb,
@@ -107,6 +108,7 @@ function App() {
       <ProtectedRoute>
         <MainApp />
       </ProtectedRoute>
+      <RecoveryCodesDialog />
     </AuthProvider>
   );
 }
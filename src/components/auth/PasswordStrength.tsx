@@ -0,0 +1,39 @@
+import type { PasswordFeedback } from "@/types/auth";
+
+const LABELS = ["Very weak", "Weak", "Fair", "Strong", "Very strong"];
+
+// Strength meter and advice for a new password (see password_policy.rs)
+export function PasswordStrength({ feedback }: { feedback: PasswordFeedback | null }) {
+  if (!feedback) return null;
+
+  return (
+    <div className="flex flex-col gap-1 text-sm">
+      <div className="flex gap-1" aria-hidden>
+        {[0, 1, 2, 3].map((step) => (
+          <div
+            key={step}
+            className={
+              "h-1.5 flex-1 rounded-full " +
+              (step < feedback.score
+                ? feedback.acceptable
+                  ? "bg-primary"
+                  : "bg-destructive"
+                : "bg-muted")
+            }
+          />
+        ))}
+      </div>
+      <p className={feedback.acceptable ? "text-muted-foreground" : "text-destructive"}>
+        {LABELS[feedback.score]}
+        {feedback.warning && ` — ${feedback.warning}`}
+      </p>
+      {feedback.suggestions.length > 0 && (
+        <ul className="text-muted-foreground list-disc pl-5">
+          {feedback.suggestions.map((suggestion) => (
+            <li key={suggestion}>{suggestion}</li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,40 @@
+import { useAuth } from "@/contexts/AuthContext";
+import {
+  AlertDialog,
+  AlertDialogAction,
+  AlertDialogContent,
+  AlertDialogDescription,
+  AlertDialogFooter,
+  AlertDialogHeader,
+  AlertDialogTitle,
+} from "@/components/ui/alert-dialog";
+
+// Shows freshly issued recovery codes exactly once (after registration or
+// regeneration). Rust stores only their hashes, so they cannot be shown again.
+export function RecoveryCodesDialog() {
+  const { recoveryCodes, dismissRecoveryCodes } = useAuth();
+
+  return (
+    <AlertDialog open={!!recoveryCodes}>
+      <AlertDialogContent>
+        <AlertDialogHeader>
+          <AlertDialogTitle>Save your recovery codes</AlertDialogTitle>
+          <AlertDialogDescription>
+            Each code can be used once to reset your password if you forget it.
+            Write them down somewhere safe — they will not be shown again.
+          </AlertDialogDescription>
+        </AlertDialogHeader>
+        <ul className="grid grid-cols-2 gap-2 rounded-md bg-muted p-4 font-mono text-sm">
+          {recoveryCodes?.map((code) => (
+            <li key={code}>{code}</li>
+          ))}
+        </ul>
+        <AlertDialogFooter>
+          <AlertDialogAction onClick={dismissRecoveryCodes}>
+            I have saved these codes
+          </AlertDialogAction>
+        </AlertDialogFooter>
+      </AlertDialogContent>
+    </AlertDialog>
+  );
+}
@@ -1,2 +1,4 @@
 export { AuthPage } from "./AuthPage";
+export { PasswordStrength } from "./PasswordStrength";
 export { ProtectedRoute } from "./ProtectedRoute";
+export { RecoveryCodesDialog } from "./RecoveryCodesDialog";
@@ -33,6 +33,7 @@ import {
   parseDurationString,
   type OcrParseResult,
 } from "@/lib/ocrParser";
+import { centsToDecimal, parseCents } from "@/lib/money";
 import { saveSessionWithOffers } from "@/services/entryService";
 import { SessionFields } from "./SessionFields";
 import type { SessionFormState, OfferFormRow } from "./types";
@@ -44,9 +45,9 @@ import type { SessionFormState, OfferFormRow } from "./types";
 function parsedSessionToFormState(parsed: OcrParseResult["session"]): SessionFormState {
   return {
     date: parsed?.date ?? "",
-    total_earnings: parsed?.total_earnings?.toString() ?? "",
-    base_pay: parsed?.base_pay?.toString() ?? "",
-    tips: parsed?.tips?.toString() ?? "",
+    total_earnings: centsToDecimal(parsed?.total_earnings ?? null),
+    base_pay: centsToDecimal(parsed?.base_pay ?? null),
+    tips: centsToDecimal(parsed?.tips ?? null),
     start_time: parsed?.start_time ?? "",
     end_time: parsed?.end_time ?? "",
     active_time: formatMinutes(parsed?.active_time ?? null),
@@ -56,7 +57,7 @@ function parsedSessionToFormState(parsed: OcrParseResult["session"]): SessionFor
     offers: (parsed?.offers ?? []).map((o) => ({
       key: crypto.randomUUID(),
       store: o.store,
-      total_earnings: o.total_earnings?.toString() ?? "",
+      total_earnings: centsToDecimal(o.total_earnings),
     })),
   };
 }
@@ -65,11 +66,6 @@ function parsedSessionToFormState(parsed: OcrParseResult["session"]): SessionFor
 // Helpers — SessionFormState → DB insert values
 // ---------------------------------------------------------------------------
 
-function toNullableFloat(s: string): number | null {
-  const v = parseFloat(s);
-  return isNaN(v) ? null : v;
-}
-
 function toNullableInt(s: string): number | null {
   const v = parseInt(s, 10);
   return isNaN(v) ? null : v;
@@ -84,7 +80,7 @@ function formOffersToDB(offers: OfferFormRow[]) {
     .filter((o) => o.store.trim() !== "")
     .map((o) => ({
       store: o.store.trim(),
-      total_earnings: toNullableFloat(o.total_earnings),
+      total_earnings: parseCents(o.total_earnings),
     }));
 }
 
@@ -152,9 +148,9 @@ export function EntryReviewModal({
           date:
             toNullableString(formState.date) ??
             new Date().toISOString().slice(0, 10),
-          total_earnings: toNullableFloat(formState.total_earnings),
-          base_pay: toNullableFloat(formState.base_pay),
-          tips: toNullableFloat(formState.tips),
+          total_earnings: parseCents(formState.total_earnings),
+          base_pay: parseCents(formState.base_pay),
+          tips: parseCents(formState.tips),
           start_time: toNullableString(formState.start_time),
           end_time: toNullableString(formState.end_time),
           active_time: parseDurationString(formState.active_time),
@@ -1,4 +1,4 @@
-import { useState } from "react"
+import { useEffect, useState } from "react"
 import { cn } from "@/lib/utils"
 import { Button } from "@/components/ui/button"
 import { Card, CardContent } from "@/components/ui/card"
@@ -10,6 +10,8 @@ import {
 } from "@/components/ui/field"
 import { Input } from "@/components/ui/input"
 import { useAuth } from "@/contexts/AuthContext"
+import { AuthService } from "@/services/authService"
+import type { RecentProfile } from "@/types/auth"
 
 export function LoginForm({
   className,
@@ -18,9 +20,16 @@ export function LoginForm({
 }: Omit<React.ComponentProps<"div">, "onSubmit"> & {
   onToggleMode?: () => void
 }) {
-  const { login } = useAuth()
+  const { login, pinUsername, unlockWithPin, usePassword } = useAuth()
   const [error, setError] = useState<string | null>(null)
   const [isLoading, setIsLoading] = useState(false)
+  const [needsTotp, setNeedsTotp] = useState(false)
+  const [username, setUsername] = useState("")
+  const [recentProfiles, setRecentProfiles] = useState<RecentProfile[]>([])
+
+  useEffect(() => {
+    AuthService.listRecentProfiles().then(setRecentProfiles)
+  }, [])
 
   const handleSubmit = async (e: React.FormEvent<HTMLFormElement>) => {
     e.preventDefault()
@@ -28,12 +37,16 @@ export function LoginForm({
     setIsLoading(true)
 
     const formData = new FormData(e.currentTarget)
-    const username = formData.get("username") as string
     const password = formData.get("password") as string
+    const remember = formData.get("remember") === "on"
+    const totpCode = (formData.get("totp_code") as string | null) ?? undefined
     
     try {
-      const result = await login(username, password)
+      const result = await login(username, password, remember, totpCode)
       if (!result.success) {
+        if (result.code === "totp_required") {
+          setNeedsTotp(true)
+        }
         setError(result.message)
       }
       // Navigation happens automatically via AuthContext
@@ -44,10 +57,77 @@ export function LoginForm({
     }
   }
 
+  const handlePinSubmit = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault()
+    setError(null)
+    setIsLoading(true)
+
+    const pin = new FormData(e.currentTarget).get("pin") as string
+    try {
+      const result = await unlockWithPin(pin)
+      if (!result.success) {
+        setError(result.message)
+      }
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
   return (
     <div className={cn("flex flex-col gap-6", className)} {...props}>
       <Card className="overflow-hidden p-0">
         <CardContent className="grid p-0 md:grid-cols-2">
+          {pinUsername ? (
+          <form className="p-6 md:p-8" onSubmit={handlePinSubmit}>
+            <FieldGroup>
+              <div className="flex flex-col items-center gap-2 text-center">
+                <h1 className="text-2xl font-bold">Welcome back</h1>
+                <p className="text-muted-foreground text-balance">
+                  Enter the PIN for {pinUsername}
+                </p>
+              </div>
+
+              {error && (
+                <div className="bg-destructive/15 text-destructive rounded-md p-3 text-sm">
+                  {error}
+                </div>
+              )}
+
+              <Field>
+                <FieldLabel htmlFor="pin">PIN</FieldLabel>
+                <Input
+                  id="pin"
+                  name="pin"
+                  type="password"
+                  inputMode="numeric"
+                  autoComplete="off"
+                  pattern="[0-9]{4,8}"
+                  maxLength={8}
+                  required
+                  autoFocus
+                  disabled={isLoading}
+                />
+              </Field>
+              <Field>
+                <Button type="submit" className="w-full" disabled={isLoading}>
+                  {isLoading ? "Unlocking..." : "Unlock"}
+                </Button>
+              </Field>
+              <FieldDescription className="text-center">
+                <a
+                  href="#"
+                  onClick={(e) => {
+                    e.preventDefault()
+                    setError(null)
+                    usePassword()
+                  }}
+                >
+                  Use password instead
+                </a>
+              </FieldDescription>
+            </FieldGroup>
+          </form>
+          ) : (
           <form className="p-6 md:p-8" onSubmit={handleSubmit}>
             <FieldGroup>
               <div className="flex flex-col items-center gap-2 text-center">
@@ -63,6 +143,26 @@ export function LoginForm({
                 </div>
               )}
 
+              {recentProfiles.length > 0 && (
+                <div className="flex flex-wrap justify-center gap-2">
+                  {recentProfiles.map((profile) => (
+                    <Button
+                      key={profile.user_id}
+                      type="button"
+                      variant={profile.username === username ? "default" : "outline"}
+                      size="sm"
+                      disabled={isLoading}
+                      onClick={() => {
+                        setUsername(profile.username)
+                        document.getElementById("password")?.focus()
+                      }}
+                    >
+                      {profile.username}
+                    </Button>
+                  ))}
+                </div>
+              )}
+
               <Field>
                 <FieldLabel htmlFor="username">Username</FieldLabel>
                 <Input
@@ -70,6 +170,8 @@ export function LoginForm({
                   name="username"
                   type="text"
                   placeholder="Enter your username"
+                  value={username}
+                  onChange={(e) => setUsername(e.target.value)}
                   required
                   disabled={isLoading}
                 />
@@ -80,6 +182,34 @@ export function LoginForm({
                 </div>
                 <Input id="password" name="password" type="password" required disabled={isLoading} />
               </Field>
+              {needsTotp && (
+                <Field>
+                  <FieldLabel htmlFor="totp_code">Authentication code</FieldLabel>
+                  <Input
+                    id="totp_code"
+                    name="totp_code"
+                    inputMode="numeric"
+                    autoComplete="one-time-code"
+                    pattern="[0-9]{6}"
+                    maxLength={6}
+                    placeholder="123456"
+                    required
+                    disabled={isLoading}
+                  />
+                </Field>
+              )}
+              <Field orientation="horizontal">
+                <input
+                  id="remember"
+                  name="remember"
+                  type="checkbox"
+                  className="size-4 accent-primary"
+                  disabled={isLoading}
+                />
+                <FieldLabel htmlFor="remember" className="font-normal">
+                  Keep me logged in on this device
+                </FieldLabel>
+              </Field>
               <Field>
                 <Button type="submit" className="w-full" disabled={isLoading}>
                   {isLoading ? "Logging in..." : "Login"}
@@ -99,6 +229,7 @@ export function LoginForm({
               </FieldDescription>
             </FieldGroup>
           </form>
+          )}
           <div className="bg-muted relative hidden md:block">
             <img
               src="/dashlens-logo.svg"
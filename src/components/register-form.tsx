@@ -1,4 +1,4 @@
-import { useState } from "react"
+import { useEffect, useState } from "react"
 import { cn } from "@/lib/utils"
 import { Button } from "@/components/ui/button"
 import { Card, CardContent } from "@/components/ui/card"
@@ -10,6 +10,9 @@ import {
 } from "@/components/ui/field"
 import { Input } from "@/components/ui/input"
 import { useAuth } from "@/contexts/AuthContext"
+import { AuthService } from "@/services/authService"
+import { PasswordStrength } from "@/components/auth/PasswordStrength"
+import type { PasswordFeedback } from "@/types/auth"
 
 export function RegisterForm({
   className,
@@ -21,6 +24,23 @@ export function RegisterForm({
   const { register } = useAuth()
   const [error, setError] = useState<string | null>(null)
   const [isLoading, setIsLoading] = useState(false)
+  const [username, setUsername] = useState("")
+  const [password, setPassword] = useState("")
+  const [feedback, setFeedback] = useState<PasswordFeedback | null>(null)
+
+  // Re-score shortly after typing stops
+  useEffect(() => {
+    if (!password) {
+      setFeedback(null)
+      return
+    }
+    const timer = setTimeout(() => {
+      AuthService.checkPasswordStrength(password, username)
+        .then(setFeedback)
+        .catch(() => setFeedback(null))
+    }, 250)
+    return () => clearTimeout(timer)
+  }, [password, username])
 
   const handleSubmit = async (e: React.FormEvent<HTMLFormElement>) => {
     e.preventDefault()
@@ -28,8 +48,6 @@ export function RegisterForm({
     setIsLoading(true)
 
     const formData = new FormData(e.currentTarget)
-    const username = formData.get("username") as string
-    const password = formData.get("password") as string
     const confirmPassword = formData.get("confirmPassword") as string
     
     // Basic validation
@@ -39,8 +57,8 @@ export function RegisterForm({
       return
     }
 
-    if (password.length < 8) {
-      setError("Password must be at least 8 characters long")
+    if (feedback && !feedback.acceptable) {
+      setError(feedback.warning ?? "Choose a stronger password")
       setIsLoading(false)
       return
     }
@@ -84,9 +102,16 @@ export function RegisterForm({
                   name="username"
                   type="text"
                   placeholder="Choose a username"
+                  value={username}
+                  onChange={(e) => setUsername(e.target.value)}
                   required
                   disabled={isLoading}
+                  minLength={3}
+                  maxLength={32}
                 />
+                <FieldDescription>
+                  3–32 letters or digits; ".", "_" and "-" allowed in between. Not case-sensitive.
+                </FieldDescription>
               </Field>
               <Field>
                 <FieldLabel htmlFor="password">Password</FieldLabel>
@@ -95,10 +120,13 @@ export function RegisterForm({
                   name="password"
                   type="password"
                   placeholder="Create a password"
+                  value={password}
+                  onChange={(e) => setPassword(e.target.value)}
                   required
                   disabled={isLoading}
                   minLength={8}
                 />
+                <PasswordStrength feedback={feedback} />
               </Field>
               <Field>
                 <FieldLabel htmlFor="confirmPassword">Confirm Password</FieldLabel>
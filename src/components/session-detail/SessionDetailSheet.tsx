@@ -7,8 +7,8 @@
 //   - Loads session + offers on open (passed in as props to avoid re-fetch)
 //   - Edit mode toggles the display values into the existing SessionFields
 //     form components so we get the same UX as the EntryReviewModal for free
-//   - On save: calls SessionService.update + replaces offers via
-//     deleteBySessionId → bulkCreate (simplest correct approach)
+//   - On save: saveSessionWithOffers overwrites the session and replaces
+//     its offers in one transaction
 //   - Fires onUpdate(updatedSession) so the parent table row refreshes
 //     without a full re-fetch
 // ---------------------------------------------------------------------------
@@ -42,7 +42,8 @@ import {
 } from "@/components/ui/alert-dialog";
 
 import { formatMinutes, parseDurationString } from "@/lib/ocrParser";
-import { SessionService, OfferService } from "@/services/entryService";
+import { centsToDecimal, formatCents, parseCents } from "@/lib/money";
+import { SessionService, saveSessionWithOffers } from "@/services/entryService";
 import { SessionFields } from "@/components/entry-review/SessionFields";
 import type { SessionFormState } from "@/components/entry-review/entry-review-types";
 import type { Session, Offer, SessionInsert } from "@/types/entries";
@@ -56,9 +57,9 @@ import type { Session, Offer, SessionInsert } from "@/types/entries";
 function sessionToFormState(session: Session, offers: Offer[]): SessionFormState {
   return {
     date:           session.date ?? "",
-    total_earnings: session.total_earnings?.toString() ?? "",
-    base_pay:       session.base_pay?.toString() ?? "",
-    tips:           session.tips?.toString() ?? "",
+    total_earnings: centsToDecimal(session.total_earnings),
+    base_pay:       centsToDecimal(session.base_pay),
+    tips:           centsToDecimal(session.tips),
     start_time:     session.start_time ?? "",
     end_time:       session.end_time ?? "",
     active_time:    formatMinutes(session.active_time),
@@ -68,16 +69,11 @@ function sessionToFormState(session: Session, offers: Offer[]): SessionFormState
     offers: offers.map((o) => ({
       key:            o.id.toString(),
       store:          o.store ?? "",
-      total_earnings: o.total_earnings?.toString() ?? "",
+      total_earnings: centsToDecimal(o.total_earnings),
     })),
   };
 }
 
-function toNullableFloat(s: string): number | null {
-  const v = parseFloat(s);
-  return isNaN(v) ? null : v;
-}
-
 function toNullableInt(s: string): number | null {
   const v = parseInt(s, 10);
   return isNaN(v) ? null : v;
@@ -90,9 +86,9 @@ function toNullableString(s: string): string | null {
 function formStateToSessionInsert(f: SessionFormState): SessionInsert {
   return {
     date:           f.date || new Date().toISOString().slice(0, 10),
-    total_earnings: toNullableFloat(f.total_earnings),
-    base_pay:       toNullableFloat(f.base_pay),
-    tips:           toNullableFloat(f.tips),
+    total_earnings: parseCents(f.total_earnings),
+    base_pay:       parseCents(f.base_pay),
+    tips:           parseCents(f.tips),
     start_time:     toNullableString(f.start_time),
     end_time:       toNullableString(f.end_time),
     active_time:    parseDurationString(f.active_time),
@@ -177,20 +173,15 @@ export function SessionDetailSheet({
     try {
       const insertData = formStateToSessionInsert(formState);
 
-      // Update the session row
-      await SessionService.update(session.id, insertData);
-
-      // Replace all offers atomically: delete existing, bulk-insert new set
-      await OfferService.deleteBySessionId(session.id);
       const offerRows = formState.offers
         .filter((o) => o.store.trim() || o.total_earnings.trim())
         .map((o) => ({
           store:          o.store.trim() || null,
-          total_earnings: toNullableFloat(o.total_earnings),
+          total_earnings: parseCents(o.total_earnings),
         }));
-      if (offerRows.length > 0) {
-        await OfferService.bulkCreate(session.id, offerRows);
-      }
+
+      // Session row and offers are replaced together or not at all
+      await saveSessionWithOffers(insertData, offerRows, session.id);
 
       // Build an optimistic updated session object so the parent table
       // refreshes immediately without a round-trip
@@ -226,7 +217,6 @@ export function SessionDetailSheet({
   // Derived display values (view mode only)
   // ---------------------------------------------------------------------------
 
-  const fmt$ = (v: number | null) => (v !== null ? `$${v.toFixed(2)}` : "—");
   const fmtTime = (v: string | null) => v ?? "—";
   const fmtMin = (v: number | null) => (v !== null ? formatMinutes(v) : "—");
 
@@ -255,7 +245,7 @@ export function SessionDetailSheet({
             {/* Earnings badge in header for quick glance */}
             {session.total_earnings !== null && (
               <Badge variant="secondary" className="text-base font-bold px-3 py-1">
-                {fmt$(session.total_earnings)}
+                {formatCents(session.total_earnings)}
               </Badge>
             )}
           </div>
@@ -278,9 +268,9 @@ export function SessionDetailSheet({
                 <div>
                   <h3 className="text-sm font-semibold mb-3">Earnings</h3>
                   <div className="grid grid-cols-2 gap-4">
-                    <ReadOnlyField label="Total"    value={fmt$(session.total_earnings)} />
-                    <ReadOnlyField label="Base Pay" value={fmt$(session.base_pay)} />
-                    <ReadOnlyField label="Tips"     value={fmt$(session.tips)} />
+                    <ReadOnlyField label="Total"    value={formatCents(session.total_earnings)} />
+                    <ReadOnlyField label="Base Pay" value={formatCents(session.base_pay)} />
+                    <ReadOnlyField label="Tips"     value={formatCents(session.tips)} />
                   </div>
                 </div>
 
@@ -326,7 +316,7 @@ export function SessionDetailSheet({
                               {offer.store ?? "Unknown store"}
                             </span>
                             <span className="text-sm font-medium tabular-nums">
-                              {fmt$(offer.total_earnings)}
+                              {formatCents(offer.total_earnings)}
                             </span>
                           </div>
                         ))}
@@ -1,15 +1,37 @@
 import React, { createContext, useContext, useEffect, useState } from "react";
-import type { AuthSession } from "@/types/auth";
+import { listen } from "@tauri-apps/api/event";
+import type { AuthSession, AuthLockedEvent, AuthChangedEvent, DashlensErrorCode } from "@/types/auth";
 import { AuthService } from "@/services/authService";
 
+// Minimum gap between activity pings sent to Rust
+const ACTIVITY_PING_MS = 30_000;
+
 interface AuthContextType {
   user: AuthSession | null;
   loading: boolean;
   isAuthenticated: boolean;
-  login: (username: string, password: string) => Promise<{ success: boolean; message: string }>;
+  canEdit: boolean;                     // false for viewers (read-only role)
+  login: (
+    username: string,
+    password: string,
+    remember?: boolean,
+    totpCode?: string,
+  ) => Promise<{ success: boolean; message: string; code?: DashlensErrorCode }>;
   register: (username: string, password: string) => Promise<{ success: boolean; message: string }>;
+  switchUser: (
+    username: string,
+    password: string,
+    totpCode?: string,
+  ) => Promise<{ success: boolean; message: string; code?: DashlensErrorCode }>;
   logout: () => Promise<void>;
+  deleteAccount: (password: string) => Promise<{ success: boolean; message: string }>;
+  pinUsername: string | null;           // remembered account waiting for its PIN
+  unlockWithPin: (pin: string) => Promise<{ success: boolean; message: string; code?: DashlensErrorCode }>;
+  usePassword: () => void;              // give up on the PIN and show the login form
   refreshUser: () => Promise<void>;
+  recoveryCodes: string[] | null;       // freshly issued, awaiting acknowledgement
+  showRecoveryCodes: (codes: string[]) => void;
+  dismissRecoveryCodes: () => void;
 }
 
 const AuthContext = createContext<AuthContextType | undefined>(undefined);
@@ -17,6 +39,8 @@ const AuthContext = createContext<AuthContextType | undefined>(undefined);
 export function AuthProvider({ children }: { children: React.ReactNode }) {
   const [user, setUser] = useState<AuthSession | null>(null);
   const [loading, setLoading] = useState(true);
+  const [recoveryCodes, setRecoveryCodes] = useState<string[] | null>(null);
+  const [pinUsername, setPinUsername] = useState<string | null>(null);
 
   const refreshUser = async () => {
     try {
@@ -30,16 +54,62 @@ export function AuthProvider({ children }: { children: React.ReactNode }) {
 
   useEffect(() => {
     const initAuth = async () => {
-      await refreshUser();
+      // Restores a remembered session, or returns the live one if any
+      const restored = await AuthService.restoreSession();
+      setUser(restored.user);
+      setPinUsername(restored.pinUsername);
       setLoading(false);
     };
     
     initAuth();
   }, []);
 
-  const login = async (username: string, password: string) => {
+  // Rust clears the session after idle timeout / max age and notifies us
+  useEffect(() => {
+    const unlisten = listen<AuthLockedEvent>("auth://locked", () => {
+      setUser(null);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Rust announces every change of logged-in user (including switches made
+  // elsewhere in the app), so all views follow the same session
+  useEffect(() => {
+    const unlisten = listen<AuthChangedEvent>("auth://changed", (event) => {
+      setUser(event.payload.user);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Forward user input as session activity (throttled)
+  useEffect(() => {
+    if (!user) return;
+    let lastPing = 0;
+    const onActivity = () => {
+      const now = Date.now();
+      if (now - lastPing < ACTIVITY_PING_MS) return;
+      lastPing = now;
+      AuthService.touchSession();
+    };
+    const events = ["pointerdown", "keydown", "scroll"] as const;
+    events.forEach((e) => window.addEventListener(e, onActivity, { passive: true }));
+    return () => {
+      events.forEach((e) => window.removeEventListener(e, onActivity));
+    };
+  }, [user]);
+
+  const login = async (username: string, password: string, remember = false, totpCode?: string) => {
     try {
-      const response = await AuthService.login({ username, password });
+      const response = await AuthService.login({
+        username,
+        password,
+        remember,
+        totp_code: totpCode,
+      });
       
       if (response.success && response.user) {
         setUser(response.user);
@@ -48,6 +118,7 @@ export function AuthProvider({ children }: { children: React.ReactNode }) {
       return {
         success: response.success,
         message: response.message,
+        code: response.code,
       };
     } catch (error) {
       console.error("Login error:", error);
@@ -64,6 +135,7 @@ export function AuthProvider({ children }: { children: React.ReactNode }) {
       
       if (response.success && response.user) {
         setUser(response.user);
+        setRecoveryCodes(response.recovery_codes ?? null);
       }
       
       return {
@@ -79,6 +151,18 @@ export function AuthProvider({ children }: { children: React.ReactNode }) {
     }
   };
 
+  const switchUser = async (username: string, password: string, totpCode?: string) => {
+    const response = await AuthService.switchUser({
+      username,
+      password,
+      remember: false,
+      totp_code: totpCode,
+    });
+    // On failure Rust has already logged the previous user out
+    setUser(response.success && response.user ? response.user : null);
+    return { success: response.success, message: response.message, code: response.code };
+  };
+
   const logout = async () => {
     try {
       await AuthService.logout();
@@ -88,16 +172,45 @@ export function AuthProvider({ children }: { children: React.ReactNode }) {
     }
   };
 
+  const unlockWithPin = async (pin: string) => {
+    const response = await AuthService.unlockWithPin(pin);
+    if (response.success && response.user) {
+      setUser(response.user);
+      setPinUsername(null);
+    } else if (response.code === "unauthenticated") {
+      // Locked out or nothing remembered any more: fall back to the password
+      setPinUsername(null);
+    }
+    return { success: response.success, message: response.message, code: response.code };
+  };
+
+  const deleteAccount = async (password: string) => {
+    const response = await AuthService.deleteAccount(password);
+    if (response.success) {
+      setUser(null);
+    }
+    return { success: response.success, message: response.message };
+  };
+
   return (
     <AuthContext.Provider
       value={{
         user,
         loading,
         isAuthenticated: !!user,
+        canEdit: user?.role === "owner",
         login,
         register,
+        switchUser,
         logout,
+        deleteAccount,
+        pinUsername,
+        unlockWithPin,
+        usePassword: () => setPinUsername(null),
         refreshUser,
+        recoveryCodes,
+        showRecoveryCodes: setRecoveryCodes,
+        dismissRecoveryCodes: () => setRecoveryCodes(null),
       }}
     >
       {children}
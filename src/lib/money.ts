@@ -0,0 +1,42 @@
+// ---------------------------------------------------------------------------
+// Money helpers
+// Amounts travel between the UI and Rust as integer cents (see money.rs), so
+// sums stay exact. Dollars only exist as text: parsed from inputs and OCR,
+// formatted for display and CSV.
+// ---------------------------------------------------------------------------
+
+/**
+ * Cents for a dollar string — "$1,234.56", "12.5", ".99" — read digit by
+ * digit, so no float rounding; a third decimal rounds half up.
+ * Returns null for blank or malformed input.
+ */
+export function parseCents(text: string): number | null {
+  const match = text.trim().replace(/[$,]/g, "").match(/^(\d*)(?:\.(\d*))?$/);
+  if (!match || (match[1] === "" && !match[2])) return null;
+  const fraction = match[2] ?? "";
+  const dollars  = match[1] === "" ? 0 : parseInt(match[1], 10);
+  const cents    = parseInt(fraction.slice(0, 2).padEnd(2, "0"), 10) + (fraction[2] >= "5" ? 1 : 0);
+  const total    = dollars * 100 + cents;
+  return Number.isSafeInteger(total) ? total : null;
+}
+
+/** "1234.56" — for form inputs and CSV; empty string for null */
+export function centsToDecimal(cents: number | null): string {
+  if (cents === null) return "";
+  const sign = cents < 0 ? "-" : "";
+  const abs  = Math.abs(cents);
+  return `${sign}${Math.floor(abs / 100)}.${String(abs % 100).padStart(2, "0")}`;
+}
+
+/** "$1,234.56" for display; "—" for null */
+export function formatCents(cents: number | null): string {
+  if (cents === null) return "—";
+  const [dollars, fraction] = centsToDecimal(Math.abs(cents)).split(".");
+  const sign = cents < 0 ? "-" : "";
+  return `${sign}$${Number(dollars).toLocaleString("en-US")}.${fraction}`;
+}
+
+/** Cents → dollars as a number, for charts; only at the display edge */
+export function centsToDollars(cents: number): number {
+  return cents / 100;
+}
@@ -4,9 +4,12 @@
 // Designed around DoorDash session (single-dash) screenshot formats.
 //
 // All fields are best-effort — missing values remain null and the user can
-// fill them in via the review form before saving.
+// fill them in via the review form before saving. Amounts are integer
+// cents, parsed exactly from the screenshot text (see lib/money.ts).
 // ---------------------------------------------------------------------------
 
+import { parseCents } from "@/lib/money";
+
 /** Months used for flexible date parsing */
 const MONTH_MAP: Record<string, number> = {
   jan: 0, feb: 1, mar: 2, apr: 3, may: 4, jun: 5,
@@ -21,14 +24,14 @@ const MONTH_MAP: Record<string, number> = {
 
 export interface ParsedOffer {
   store: string;
-  total_earnings: number | null;
+  total_earnings: number | null;     // cents
 }
 
 export interface ParsedSession {
   date: string | null;               // ISO "YYYY-MM-DD"
-  total_earnings: number | null;
-  base_pay: number | null;           // "DoorDash pay" — only present on expanded earnings screenshots
-  tips: number | null;               // "Customer tips" — only present on expanded earnings screenshots
+  total_earnings: number | null;     // cents
+  base_pay: number | null;           // cents; "DoorDash pay" — only present on expanded earnings screenshots
+  tips: number | null;               // cents; "Customer tips" — only present on expanded earnings screenshots
   start_time: string | null;         // "HH:MM" 24h
   end_time: string | null;           // "HH:MM" 24h
   active_time: number | null;        // minutes
@@ -49,14 +52,12 @@ export interface OcrParseResult {
 // ---------------------------------------------------------------------------
 
 /**
- * Extracts the first dollar amount from a string.
+ * Extracts the first dollar amount from a string, in cents.
  * Handles "$1,234.56", "1234.56", etc.
  */
 function parseCurrency(text: string): number | null {
   const match = text.match(/\$?([\d,]+\.?\d{0,2})/);
-  if (!match) return null;
-  const val = parseFloat(match[1].replace(",", ""));
-  return isNaN(val) ? null : val;
+  return match ? parseCents(match[1]) : null;
 }
 
 /**
@@ -47,6 +47,7 @@ import {
   type ChartConfig,
 } from "@/components/ui/chart";
 
+import { centsToDollars, formatCents } from "@/lib/money";
 import { SessionService, OfferService } from "@/services/entryService";
 import type { Session, Offer } from "@/types/entries";
 
@@ -97,6 +98,8 @@ function computeMetrics(data: SessionWithOffers[], range: TimeRange) {
   const sessions = filtered.map((d) => d.session);
 
   // ── Earnings ─────────────────────────────────────────────────────────────
+  // Amounts are integer cents, so these sums are exact; rates below are
+  // cents per unit and only become dollars when formatted or charted.
   const totalEarnings = sessions.reduce((s, x) => s + (x.total_earnings ?? 0), 0);
   const totalBasePay  = sessions.reduce((s, x) => s + (x.base_pay ?? 0), 0);
   const totalTips     = sessions.reduce((s, x) => s + (x.tips ?? 0), 0);
@@ -136,7 +139,7 @@ function computeMetrics(data: SessionWithOffers[], range: TimeRange) {
     .map(([date, earnings]) => ({
       date,
       label:    format(parseISO(date), "MMM d"),
-      earnings: parseFloat(earnings.toFixed(2)),
+      earnings: centsToDollars(earnings),
     }));
 
   // ── Chart: day-of-week average earnings ───────────────────────────────────
@@ -153,7 +156,7 @@ function computeMetrics(data: SessionWithOffers[], range: TimeRange) {
   );
   const dowEarnings = DAY_NAMES.map((name, i) => ({
     day:      name,
-    avg:      dowMap[i] ? parseFloat((dowMap[i].total / dowMap[i].count).toFixed(2)) : 0,
+    avg:      dowMap[i] ? centsToDollars(Math.round(dowMap[i].total / dowMap[i].count)) : 0,
     sessions: dowMap[i]?.count ?? 0,
     // Flag the peak day for a distinct bar color
     isPeak:   dowMap[i]
@@ -173,7 +176,7 @@ function computeMetrics(data: SessionWithOffers[], range: TimeRange) {
   const topStores = Object.entries(storeMap)
     .map(([store, { earnings, count }]) => ({
       store,
-      earnings: parseFloat(earnings.toFixed(2)),
+      earnings: centsToDollars(earnings),
       count,
     }))
     .sort((a, b) => b.earnings - a.earnings)
@@ -192,9 +195,10 @@ function computeMetrics(data: SessionWithOffers[], range: TimeRange) {
 // Formatting helpers
 // ---------------------------------------------------------------------------
 
+// Cents (or cents per hour / session / delivery) as dollars
 function fmt$(value: number | null): string {
   if (value === null || isNaN(value)) return "—";
-  return `$${value.toFixed(2)}`;
+  return formatCents(Math.round(value));
 }
 
 function fmtPct(value: number | null): string {
@@ -3,8 +3,9 @@
 // Flat table of all sessions — every DB column visible. Clicking a row opens
 // the SessionDetailSheet for full read/edit view of that session + its offers.
 //
-// Filtering: text search against date + time fields, date-range presets + custom.
-// Export: CSV of filtered sessions with their offers.
+// Filtering: store search and date-range presets + custom, applied in Rust by
+// query_sessions; rows arrive a page at a time ("Load more").
+// Export: CSV of all filtered sessions with their offers.
 // ---------------------------------------------------------------------------
 
 import * as React from "react";
@@ -46,8 +47,10 @@ import { ScrollArea } from "@/components/ui/scroll-area";
 import { Skeleton } from "@/components/ui/skeleton";
 import { Alert, AlertDescription, AlertTitle } from "@/components/ui/alert";
 
+import { centsToDecimal, formatCents } from "@/lib/money";
 import { SessionService, OfferService } from "@/services/entryService";
-import type { Session, Offer } from "@/types/entries";
+import { AuditService } from "@/services/auditService";
+import type { Session, Offer, SessionCursor, SessionFilter, SessionTotals } from "@/types/entries";
 import { SessionDetailSheet } from "@/components/session-detail/SessionDetailSheet";
 
 // ---------------------------------------------------------------------------
@@ -76,10 +79,6 @@ function getPresetDateRange(preset: DateRangePreset): { from: Date; to: Date } |
 // Formatting helpers
 // ---------------------------------------------------------------------------
 
-function fmt$(v: number | null): string {
-  return v !== null ? `$${v.toFixed(2)}` : "—";
-}
-
 function fmtMinutes(v: number | null): string {
   if (v === null) return "—";
   const h = Math.floor(v / 60);
@@ -89,6 +88,15 @@ function fmtMinutes(v: number | null): string {
   return `${h}h ${m}m`;
 }
 
+// Tauri commands reject with a DashlensError ({ code, message, details })
+function errorMessage(err: unknown, fallback: string): string {
+  if (err instanceof Error) return err.message;
+  if (err && typeof err === "object" && "message" in err) {
+    return String((err as { message: unknown }).message);
+  }
+  return fallback;
+}
+
 // ---------------------------------------------------------------------------
 // CSV Export
 // ---------------------------------------------------------------------------
@@ -109,7 +117,7 @@ async function exportToCsv(sessions: Session[]): Promise<void> {
     const base = [
       session.id, session.date,
       session.start_time ?? "", session.end_time ?? "",
-      session.total_earnings ?? "", session.base_pay ?? "", session.tips ?? "",
+      centsToDecimal(session.total_earnings), centsToDecimal(session.base_pay), centsToDecimal(session.tips),
       session.active_time ?? "", session.total_time ?? "",
       session.offers_count ?? "", session.deliveries ?? "",
     ];
@@ -117,7 +125,7 @@ async function exportToCsv(sessions: Session[]): Promise<void> {
       rows.push([...base, "", ""].join(","));
     } else {
       offers.forEach((o) =>
-        rows.push([...base, `"${o.store ?? ""}"`, o.total_earnings ?? ""].join(","))
+        rows.push([...base, `"${o.store ?? ""}"`, centsToDecimal(o.total_earnings)].join(","))
       );
     }
   }
@@ -215,7 +223,10 @@ function TableSkeleton() {
 export function Data() {
   // -- Sessions state --
   const [sessions, setSessions]     = React.useState<Session[]>([]);
+  const [totals, setTotals]         = React.useState<SessionTotals | null>(null);
+  const [nextCursor, setNextCursor] = React.useState<SessionCursor | null>(null);
   const [loading, setLoading]       = React.useState(true);
+  const [loadingMore, setLoadingMore] = React.useState(false);
   const [error, setError]           = React.useState<string | null>(null);
   const [exporting, setExporting]   = React.useState(false);
 
@@ -233,51 +244,52 @@ export function Data() {
   // Cache offers per session to avoid redundant fetches within the same page load
   const offersCache = React.useRef<Map<number, Offer[]>>(new Map());
 
+  // -- Filter --
+
+  const filter = React.useMemo<SessionFilter>(() => {
+    const range = datePreset === "custom" ? customDateRange : getPresetDateRange(datePreset);
+    return {
+      date_from: range?.from ? format(range.from, "yyyy-MM-dd") : undefined,
+      date_to:   range?.from ? format(range.to ?? new Date(), "yyyy-MM-dd") : undefined,
+      store:     searchQuery.trim() || undefined,
+    };
+  }, [searchQuery, datePreset, customDateRange]);
+
   // -- Data fetch --
 
   const fetchSessions = React.useCallback(async () => {
     setLoading(true);
     setError(null);
     try {
-      const data = await SessionService.getAll();
-      setSessions(data);
+      const page = await SessionService.query(filter);
+      setSessions(page.sessions);
+      setTotals(page.totals);
+      setNextCursor(page.next_cursor);
     } catch (err) {
       console.error("Failed to load sessions:", err);
-      setError(err instanceof Error ? err.message : "Failed to load sessions from the database.");
+      setError(errorMessage(err, "Failed to load sessions from the database."));
     } finally {
       setLoading(false);
     }
-  }, []);
+  }, [filter]);
 
   React.useEffect(() => { fetchSessions(); }, [fetchSessions]);
 
-  // -- Filtered sessions --
-
-  const filteredSessions = React.useMemo(() => {
-    let filtered = sessions;
-
-    if (searchQuery.trim()) {
-      const q = searchQuery.toLowerCase();
-      filtered = filtered.filter(
-        (s) =>
-          s.date.includes(q) ||
-          (s.start_time ?? "").toLowerCase().includes(q) ||
-          (s.end_time ?? "").toLowerCase().includes(q)
-      );
-    }
-
-    const range = datePreset === "custom" ? customDateRange : getPresetDateRange(datePreset);
-    if (range?.from) {
-      const fromMs = range.from.getTime();
-      const toMs   = range.to?.getTime() ?? Date.now();
-      filtered = filtered.filter((s) => {
-        const t = parseISO(s.date).getTime();
-        return t >= fromMs && t <= toMs;
-      });
+  async function handleLoadMore() {
+    if (!nextCursor) return;
+    setLoadingMore(true);
+    try {
+      const page = await SessionService.query(filter, "date_desc", nextCursor);
+      setSessions((prev) => [...prev, ...page.sessions]);
+      setTotals(page.totals);
+      setNextCursor(page.next_cursor);
+    } catch (err) {
+      console.error("Failed to load more sessions:", err);
+      setError(errorMessage(err, "Failed to load more sessions."));
+    } finally {
+      setLoadingMore(false);
     }
-
-    return filtered;
-  }, [sessions, searchQuery, datePreset, customDateRange]);
+  }
 
   // -- Row click → open sheet --
 
@@ -316,6 +328,7 @@ export function Data() {
 
   function handleSessionDelete(sessionId: number) {
     setSessions((prev) => prev.filter((s) => s.id !== sessionId));
+    setTotals((prev) => (prev ? { ...prev, count: prev.count - 1 } : prev));
     offersCache.current.delete(sessionId);
   }
 
@@ -324,7 +337,9 @@ export function Data() {
   async function handleExport() {
     setExporting(true);
     try {
-      await exportToCsv(filteredSessions);
+      const matching = await SessionService.queryAll(filter);
+      await exportToCsv(matching);
+      await AuditService.recordExport("csv", matching.length);
     } catch (err) {
       console.error("CSV export failed:", err);
     } finally {
@@ -370,7 +385,7 @@ export function Data() {
             <div className="relative flex-1 max-w-sm">
               <Search className="absolute left-3 top-1/2 -translate-y-1/2 size-4 text-muted-foreground" />
               <Input
-                placeholder="Search by date or time…"
+                placeholder="Search by store…"
                 value={searchQuery}
                 onChange={(e) => setSearchQuery(e.target.value)}
                 className="pl-9"
@@ -387,7 +402,7 @@ export function Data() {
             <Button
               variant="outline"
               onClick={handleExport}
-              disabled={exporting || filteredSessions.length === 0}
+              disabled={exporting || !totals?.count}
               className="gap-2"
             >
               {exporting ? <RefreshCw className="size-4 animate-spin" /> : <Download className="size-4" />}
@@ -402,7 +417,7 @@ export function Data() {
         <p className="text-sm text-muted-foreground">
           {loading
             ? "Loading sessions…"
-            : `Showing ${filteredSessions.length} of ${sessions.length} sessions`}
+            : `Showing ${sessions.length} of ${totals?.count ?? 0} sessions`}
         </p>
       </div>
 
@@ -427,14 +442,14 @@ export function Data() {
             <TableBody>
               {loading ? (
                 <TableSkeleton />
-              ) : filteredSessions.length === 0 ? (
+              ) : sessions.length === 0 ? (
                 <TableRow>
                   <TableCell colSpan={10} className="h-24 text-center text-muted-foreground">
                     No sessions found. Try adjusting your filters.
                   </TableCell>
                 </TableRow>
               ) : (
-                filteredSessions.map((session) => (
+                sessions.map((session) => (
                   <TableRow
                     key={session.id}
                     className="cursor-pointer"
@@ -450,13 +465,13 @@ export function Data() {
                       {session.end_time ?? "—"}
                     </TableCell>
                     <TableCell className="font-medium tabular-nums">
-                      {fmt$(session.total_earnings)}
+                      {formatCents(session.total_earnings)}
                     </TableCell>
                     <TableCell className="tabular-nums text-muted-foreground text-sm">
-                      {fmt$(session.base_pay)}
+                      {formatCents(session.base_pay)}
                     </TableCell>
                     <TableCell className="tabular-nums text-muted-foreground text-sm">
-                      {fmt$(session.tips)}
+                      {formatCents(session.tips)}
                     </TableCell>
                     <TableCell className="tabular-nums text-sm">
                       {fmtMinutes(session.active_time)}
@@ -479,6 +494,14 @@ export function Data() {
               )}
             </TableBody>
           </Table>
+          {nextCursor && !loading && (
+            <div className="flex justify-center p-4">
+              <Button variant="outline" onClick={handleLoadMore} disabled={loadingMore} className="gap-2">
+                {loadingMore && <RefreshCw className="size-4 animate-spin" />}
+                Load more
+              </Button>
+            </div>
+          )}
         </ScrollArea>
       </Card>
 
@@ -0,0 +1,24 @@
+// ---------------------------------------------------------------------------
+// Audit Service
+// Wrapper over the audit commands (src-tauri/src/audit.rs). Entries are
+// written on the Rust side; the webview only reports exports it produces.
+// ---------------------------------------------------------------------------
+
+import { invoke } from "@tauri-apps/api/core";
+import type { AuditPage, AuditVerification } from "@/types/audit";
+
+export const AuditService = {
+  /** Newest-first page; pass the previous page's next_before_id to continue. */
+  async list(beforeId?: number, limit?: number): Promise<AuditPage> {
+    return invoke<AuditPage>("list_audit_log", { beforeId, limit });
+  },
+
+  /** Check the whole hash chain and report the first broken entry, if any. */
+  async verify(): Promise<AuditVerification> {
+    return invoke<AuditVerification>("verify_audit_log");
+  },
+
+  async recordExport(format: string, sessionCount: number): Promise<void> {
+    await invoke("record_export", { export: { format, session_count: sessionCount } });
+  },
+};
@@ -1,153 +1,58 @@
 import { invoke } from "@tauri-apps/api/core";
-import Database from "@tauri-apps/plugin-sql";
 import type {
   AuthSession,
   RegisterRequest,
   LoginRequest,
+  ChangePasswordRequest,
   AuthResponse,
+  CalibrationResult,
+  DashlensError,
+  TotpEnrollment,
+  RegisterResponse,
+  RestoreResult,
+  RecoverAccountRequest,
+  UsernameConflict,
+  RecentProfile,
+  Viewer,
+  PasswordFeedback,
 } from "@/types/auth";
 
-let db: Database | null = null;
-
-// Initialize database connection
-async function getDb(): Promise<Database> {
-  if (!db) {
-    db = await Database.load("sqlite:dashlens.db");
+// Tauri commands reject with a DashlensError ({ code, message, details });
+// plain strings are still accepted from plugin commands
+function errorMessage(error: unknown, fallback: string): string {
+  if (typeof error === "string") return error;
+  if (error instanceof Error) return error.message;
+  if (error && typeof error === "object" && "message" in error) {
+    return String((error as { message: unknown }).message);
   }
-  return db;
+  return fallback;
 }
 
 export class AuthService {
-  // Hash a password using Rust Argon2
-  private static async hashPassword(password: string): Promise<string> {
-    const result = await invoke<{ hash: string }>("hash_password", { password });
-    return result.hash;
-  }
-
-  // Verify a password using Rust Argon2
-  private static async verifyPassword(password: string, hash: string): Promise<boolean> {
-    const result = await invoke<{ valid: boolean }>("verify_password", {
-      request: { password, hash },
-    });
-    return result.valid;
-  }
-
-  // Register a new user
+  // Register a new user (hashing, insert and session handled in Rust)
   static async register(request: RegisterRequest): Promise<AuthResponse> {
     try {
-      // Validate input
-      if (!request.username.trim()) {
-        return {
-          success: false,
-          message: "Username cannot be empty",
-          user: undefined,
-        };
-      }
-
-      if (request.password.length < 8) {
-        return {
-          success: false,
-          message: "Password must be at least 8 characters",
-          user: undefined,
-        };
-      }
-
-      const database = await getDb();
-
-      // Check if user already exists
-      const existingUsers = await database.select<Array<{ id: number }>>(
-        "SELECT id FROM users WHERE username = $1",
-        [request.username]
-      );
-
-      if (existingUsers.length > 0) {
-        return {
-          success: false,
-          message: "Username already exists",
-          user: undefined,
-        };
-      }
-
-      // Hash the password
-      const passwordHash = await this.hashPassword(request.password);
-
-      // Insert the user
-      const result = await database.execute(
-        "INSERT INTO users (username, password_hash) VALUES ($1, $2)",
-        [request.username, passwordHash]
-      );
-
-      // Get the inserted user's ID
-      const userId = result.lastInsertId;
-
-      // Create session
-      const session: AuthSession = {
-        user_id: userId,
-        username: request.username,
-        logged_in: true,
-      };
-
-      // Set session in Rust state
-      await invoke("set_session", { session });
-
+      const result = await invoke<RegisterResponse>("register", { request });
       return {
         success: true,
         message: "Registration successful",
-        user: session,
+        user: result.user,
+        recovery_codes: result.recovery_codes,
       };
     } catch (error) {
       console.error("Registration error:", error);
       return {
         success: false,
-        message: error instanceof Error ? error.message : "Registration failed",
+        message: errorMessage(error, "Registration failed"),
         user: undefined,
       };
     }
   }
 
-  // Login a user
+  // Login a user (credentials verified in Rust)
   static async login(request: LoginRequest): Promise<AuthResponse> {
     try {
-      const database = await getDb();
-
-      // Fetch user from database
-      const users = await database.select<
-        Array<{ id: number; username: string; password_hash: string }>
-      >("SELECT id, username, password_hash FROM users WHERE username = $1", [
-        request.username,
-      ]);
-
-      if (users.length === 0) {
-        return {
-          success: false,
-          message: "Invalid credentials",
-          user: undefined,
-        };
-      }
-
-      const user = users[0];
-
-      // Verify password
-      const isValid = await this.verifyPassword(request.password, user.password_hash);
-
-      if (!isValid) {
-        return {
-          success: false,
-          message: "Invalid credentials",
-          user: undefined,
-        };
-      }
-
-      // Create session
-      const session: AuthSession = {
-        user_id: user.id,
-        username: user.username,
-        logged_in: true,
-      };
-
-      // Set session in Rust state
-      await invoke("set_session", { session });
-
+      const session = await invoke<AuthSession>("login", { request });
       return {
         success: true,
         message: "Login successful",
@@ -157,12 +62,49 @@ export class AuthService {
       console.error("Login error:", error);
       return {
         success: false,
-        message: error instanceof Error ? error.message : "Login failed",
+        message: errorMessage(error, "Login failed"),
+        user: undefined,
+        code: (error as Partial<DashlensError> | null)?.code,
+      };
+    }
+  }
+
+  // Lock the current user and log in as another. The current user is logged
+  // out even if this fails; a TOTP follow-up goes through login().
+  static async switchUser(request: LoginRequest): Promise<AuthResponse> {
+    try {
+      const session = await invoke<AuthSession>("switch_user", { request });
+      return {
+        success: true,
+        message: `Switched to ${session.username}`,
+        user: session,
+      };
+    } catch (error) {
+      console.error("Switch user error:", error);
+      return {
+        success: false,
+        message: errorMessage(error, "Switching user failed"),
         user: undefined,
+        code: (error as Partial<DashlensError> | null)?.code,
       };
     }
   }
 
+  // Profiles recently used on this device, most recent first
+  static async listRecentProfiles(): Promise<RecentProfile[]> {
+    try {
+      return await invoke<RecentProfile[]>("list_recent_profiles");
+    } catch (error) {
+      console.error("List recent profiles error:", error);
+      return [];
+    }
+  }
+
+  // Remove the logged-in user from this device's recent profiles
+  static async forgetRecentProfile(): Promise<void> {
+    await invoke("forget_recent_profile");
+  }
+
   // Logout the current user
   static async logout(): Promise<AuthResponse> {
     try {
@@ -181,6 +123,178 @@ export class AuthService {
     }
   }
 
+  // Change the logged-in user's password (current password re-verified in Rust)
+  static async changePassword(request: ChangePasswordRequest): Promise<AuthResponse> {
+    try {
+      await invoke("change_password", { request });
+      return {
+        success: true,
+        message: "Password changed",
+        user: undefined,
+      };
+    } catch (error) {
+      console.error("Change password error:", error);
+      return {
+        success: false,
+        message: errorMessage(error, "Password change failed"),
+        user: undefined,
+      };
+    }
+  }
+
+  // Permanently delete the logged-in account (requires the password)
+  static async deleteAccount(password: string): Promise<AuthResponse> {
+    try {
+      await invoke("delete_account", { request: { password } });
+      return {
+        success: true,
+        message: "Account deleted",
+        user: undefined,
+      };
+    } catch (error) {
+      console.error("Delete account error:", error);
+      return {
+        success: false,
+        message: errorMessage(error, "Account deletion failed"),
+        user: undefined,
+      };
+    }
+  }
+
+  // Score a prospective password against the Rust password policy
+  static async checkPasswordStrength(password: string, username?: string): Promise<PasswordFeedback> {
+    return invoke<PasswordFeedback>("check_password_strength", { password, username });
+  }
+
+  // Benchmark this device and store Argon2 parameters for new hashes
+  static async calibrateArgon2(targetMs?: number): Promise<CalibrationResult> {
+    return invoke<CalibrationResult>("calibrate_argon2", { targetMs });
+  }
+
+  // Start TOTP enrollment; returns the secret, otpauth URI and QR code
+  static async beginTotpEnrollment(): Promise<TotpEnrollment> {
+    return invoke<TotpEnrollment>("begin_totp_enrollment");
+  }
+
+  // Activate TOTP once a code from the authenticator app checks out
+  static async confirmTotpEnrollment(code: string): Promise<void> {
+    await invoke("confirm_totp_enrollment", { code });
+  }
+
+  static async disableTotp(password: string): Promise<void> {
+    await invoke("disable_totp", { request: { password } });
+  }
+
+  // Reset a forgotten password with a single-use recovery code
+  static async recoverAccount(request: RecoverAccountRequest): Promise<AuthResponse> {
+    try {
+      const result = await invoke<{ remaining_codes: number }>("recover_account", { request });
+      return {
+        success: true,
+        message: `Password reset. ${result.remaining_codes} recovery codes left.`,
+        user: undefined,
+      };
+    } catch (error) {
+      console.error("Recover account error:", error);
+      return {
+        success: false,
+        message: errorMessage(error, "Account recovery failed"),
+        user: undefined,
+      };
+    }
+  }
+
+  // Replace all recovery codes with a new set (requires the password)
+  static async regenerateRecoveryCodes(password: string): Promise<string[]> {
+    return invoke<string[]>("regenerate_recovery_codes", { request: { password } });
+  }
+
+  // Encrypt this account's earnings at rest. Returns a new set of recovery
+  // codes (the old ones cannot unlock the encrypted data) to show the user.
+  static async enableEncryption(password: string): Promise<string[]> {
+    return invoke<string[]>("enable_encryption", { request: { password } });
+  }
+
+  static async disableEncryption(password: string): Promise<void> {
+    await invoke("disable_encryption", { request: { password } });
+  }
+
+  static async encryptionStatus(): Promise<boolean> {
+    return invoke<boolean>("encryption_status");
+  }
+
+  // Add a read-only account that sees the logged-in owner's data
+  static async createViewer(username: string, password: string): Promise<Viewer> {
+    return invoke<Viewer>("create_viewer", { request: { username, password } });
+  }
+
+  static async listViewers(): Promise<Viewer[]> {
+    return invoke<Viewer[]>("list_viewers");
+  }
+
+  static async removeViewer(viewerId: number): Promise<void> {
+    await invoke("remove_viewer", { viewerId });
+  }
+
+  // Accounts renamed when usernames became case-insensitive
+  static async usernameConflicts(): Promise<UsernameConflict[]> {
+    return invoke<UsernameConflict[]>("list_username_conflicts");
+  }
+
+  // Log the remembered user back in at startup, if their token is valid.
+  // Accounts with a PIN are not logged in; pinUsername asks for the PIN.
+  static async restoreSession(): Promise<RestoreResult> {
+    try {
+      const user = await invoke<AuthSession | null>("restore_session");
+      return { user, pinUsername: null };
+    } catch (error) {
+      const rejection = error as Partial<DashlensError> | null;
+      if (rejection?.code === "pin_required") {
+        return { user: null, pinUsername: String(rejection.details?.username ?? "") };
+      }
+      console.error("Restore session error:", error);
+      return { user: null, pinUsername: null };
+    }
+  }
+
+  // Unlock the remembered account with its quick-unlock PIN
+  static async unlockWithPin(pin: string): Promise<AuthResponse> {
+    try {
+      const session = await invoke<AuthSession>("unlock_with_pin", { request: { pin } });
+      return { success: true, message: "Unlocked", user: session };
+    } catch (error) {
+      console.error("PIN unlock error:", error);
+      return {
+        success: false,
+        message: errorMessage(error, "PIN unlock failed"),
+        user: undefined,
+        code: (error as Partial<DashlensError> | null)?.code,
+      };
+    }
+  }
+
+  // Set a 4–8 digit quick-unlock PIN (requires the password)
+  static async setPin(password: string, pin: string): Promise<void> {
+    await invoke("set_pin", { request: { password, pin } });
+  }
+
+  static async removePin(): Promise<void> {
+    await invoke("remove_pin");
+  }
+
+  static async pinStatus(): Promise<boolean> {
+    return invoke<boolean>("pin_status");
+  }
+
+  // Report user activity so the Rust side defers the idle lock
+  static async touchSession(): Promise<void> {
+    try {
+      await invoke("touch_session");
+    } catch {
+      // Session already locked — the auth://locked listener handles it
+    }
+  }
+
   // Get the current logged-in user
   static async getCurrentUser(): Promise<AuthSession | null> {
     try {
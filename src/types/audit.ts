@@ -0,0 +1,34 @@
+// Mirrors the structs in src-tauri/src/audit.rs
+
+export type AuditEventName =
+  | "login_succeeded"
+  | "login_failed"
+  | "logout"
+  | "password_changed"
+  | "session_created"
+  | "session_updated"
+  | "session_deleted"
+  | "export";
+
+export interface AuditEntry {
+  id: number;
+  created_at: number;  // unix seconds
+  user_id: number | null;
+  username: string;
+  event: AuditEventName;
+  details: string;     // JSON object
+  prev_hash: string;
+  hash: string;
+}
+
+export interface AuditPage {
+  entries: AuditEntry[];
+  next_before_id: number | null;  // cursor for the next (older) page
+}
+
+export interface AuditVerification {
+  valid: boolean;
+  checked: number;
+  first_broken_id: number | null;
+  reason: string | null;
+}
@@ -1,7 +1,34 @@
+// Owners manage their own data; viewers can only read their owner's (see roles.rs)
+export type Role = "owner" | "viewer";
+
 export interface AuthSession {
   user_id: number;
   username: string;
+  role: Role;
+  owner_id: number;       // whose data the session reads; user_id for owners
   logged_in: boolean;
+  issued_at: number;      // unix seconds
+  last_activity: number;  // unix seconds
+}
+
+// Payload of the `auth://locked` event emitted when a session lapses
+export interface AuthLockedEvent {
+  user_id: number;
+  username: string;
+  reason: "idle" | "expired";
+}
+
+// Payload of the `auth://changed` event, sent whenever the logged-in user changes
+export interface AuthChangedEvent {
+  user: AuthSession | null;
+  reason: "login" | "register" | "restore" | "pin" | "switch" | "logout" | "locked" | "deleted";
+}
+
+// A profile recently used on this device (login screen picker)
+export interface RecentProfile {
+  user_id: number;
+  username: string;
+  last_used_at: number; // unix seconds
 }
 
 export interface RegisterRequest {
@@ -12,10 +39,109 @@ export interface RegisterRequest {
 export interface LoginRequest {
   username: string;
   password: string;
+  remember?: boolean;  // issue a remember-me token for restore_session
+  totp_code?: string;  // authenticator code, once TOTP is enabled
+}
+
+export interface ChangePasswordRequest {
+  current_password: string;
+  new_password: string;
 }
 
 export interface AuthResponse {
   success: boolean;
   message: string;
   user?: AuthSession;
+  code?: DashlensErrorCode;  // set when login is rejected
+  recovery_codes?: string[];  // issued on registration, shown once
+}
+
+// Outcome of restore_session at startup: a live session, or the remembered
+// account waiting for its quick-unlock PIN
+export interface RestoreResult {
+  user: AuthSession | null;
+  pinUsername: string | null;
+}
+
+// Returned by the `register` command
+export interface RegisterResponse {
+  user: AuthSession;
+  recovery_codes: string[];
+}
+
+export interface RecoverAccountRequest {
+  username: string;
+  code: string;
+  new_password: string;
+}
+
+// Rejection payload of every Tauri command (see error.rs)
+export type DashlensErrorCode =
+  | "unauthenticated"
+  | "invalid_credentials"
+  | "locked"
+  | "totp_required"
+  | "pin_required"
+  | "permission_denied"
+  | "weak_password"
+  | "validation"
+  | "conflict"
+  | "not_found"
+  | "database"
+  | "ocr"
+  | "io"
+  | "internal";
+
+export interface DashlensError {
+  code: DashlensErrorCode;
+  message: string;
+  details: Record<string, unknown> | null;  // e.g. { retry_after_secs } when locked
+}
+
+// Strength estimate for a new password (see password_policy.rs); also the
+// `details` of a weak_password error
+export interface PasswordFeedback {
+  score: number;          // 0 (trivial) – 4 (very strong)
+  guesses_log10: number;
+  acceptable: boolean;
+  breached: boolean;      // on the bundled breached-password list
+  warning: string | null;
+  suggestions: string[];
+}
+
+// Argon2 parameters used for new password hashes (see password.rs)
+export interface Argon2Policy {
+  variant: "argon2d" | "argon2i" | "argon2id";
+  m_cost: number;  // KiB
+  t_cost: number;  // iterations
+  p_cost: number;  // lanes
+}
+
+export interface CalibrationResult {
+  policy: Argon2Policy;
+  measured_ms: number;
+}
+
+// Returned by begin_totp_enrollment
+export interface TotpEnrollment {
+  secret: string;       // base32, for manual entry
+  otpauth_uri: string;
+  qr_svg: string;
+}
+
+// Account renamed by the username normalization migration (see username.rs)
+export interface UsernameConflict {
+  user_id: number;
+  original_username: string;
+  renamed_to: string;
+  conflicts_with: number;  // id of the account that kept the name
+  detected_at: number;     // unix seconds
+  resolved_at: number | null;  // first login after the rename; until then the old name still works
+}
+
+// Read-only account attached to the logged-in owner
+export interface Viewer {
+  user_id: number;
+  username: string;
+  created_at: string | null;
 }
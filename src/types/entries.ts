@@ -2,15 +2,16 @@
 // DB Entity Types
 // Mirror the SQLite schema defined in lib.rs migrations.
 // All duration fields are stored as INTEGER (minutes).
-// All earnings fields are stored as REAL (dollars).
+// All earnings fields are integer cents (INTEGER since v13, Money in Rust);
+// see lib/money.ts for parsing and formatting.
 // ---------------------------------------------------------------------------
 
 export interface Session {
   id: number;
   date: string;                  // ISO "YYYY-MM-DD"
-  total_earnings: number | null;
-  base_pay: number | null;       // "DoorDash pay" — only on expanded earnings screenshots
-  tips: number | null;           // "Customer tips" — only on expanded earnings screenshots
+  total_earnings: number | null; // cents
+  base_pay: number | null;       // cents; "DoorDash pay" — only on expanded earnings screenshots
+  tips: number | null;           // cents; "Customer tips" — only on expanded earnings screenshots
   start_time: string | null;     // "HH:MM" 24h
   end_time: string | null;       // "HH:MM" 24h
   active_time: number | null;    // minutes
@@ -24,7 +25,7 @@ export interface Offer {
   id: number;
   session_id: number;            // FK → sessions.id
   store: string | null;
-  total_earnings: number | null;
+  total_earnings: number | null; // cents
   created_at: string;
 }
 
@@ -42,3 +43,45 @@ export type OfferInsert = Omit<Offer, "id" | "created_at">;
 export interface SessionWithOffers extends Session {
   offers: Offer[];
 }
+
+// ---------------------------------------------------------------------------
+// query_sessions — filter, sort and keyset pagination (see session_query.rs)
+// ---------------------------------------------------------------------------
+
+export interface SessionFilter {
+  date_from?: string;      // inclusive "YYYY-MM-DD"
+  date_to?: string;        // inclusive
+  min_earnings?: number;   // cents
+  max_earnings?: number;   // cents
+  store?: string;          // substring of any offer's store, case-insensitive
+  weekdays?: number[];     // 0 = Sunday … 6 = Saturday
+  start_from?: string;     // "HH:MM"; the window may wrap past midnight
+  start_to?: string;       // exclusive
+}
+
+export type SessionSort =
+  | "date_desc" | "date_asc"
+  | "earnings_desc" | "earnings_asc"
+  | "duration_desc" | "duration_asc";
+
+// Opaque — pass next_cursor back unchanged to fetch the following page
+export interface SessionCursor {
+  value: number | string | null;
+  id: number;
+}
+
+export interface SessionTotals {
+  count: number;
+  total_earnings: number;  // cents
+  base_pay: number;        // cents
+  tips: number;            // cents
+  active_time: number;     // minutes
+  total_time: number;      // minutes
+  deliveries: number;
+}
+
+export interface SessionPage {
+  sessions: Session[];
+  next_cursor: SessionCursor | null;
+  totals: SessionTotals;   // over all matching sessions, not just this page
+}